@@ -224,15 +224,15 @@ impl From<Algorithm> for zip::CompressionMethod {
     }
 }
 
-impl ToString for Algorithm {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let algorithm = match self {
             Algorithm::Store => "STORE",
             Algorithm::BZip2 => "BZIP2",
             Algorithm::Deflate => "DEFLATE",
             Algorithm::ZStd => "ZSTD",
-        }
-        .into()
+        };
+        f.write_str(algorithm)
     }
 }
 
@@ -0,0 +1,119 @@
+use crate::prelude::*;
+
+/// Problem found in a manifest, reported along with its file and key
+/// (used by the version policy audit and the metadata validation)
+#[derive(Debug)]
+pub struct Violation {
+    pub file: PathBuf,
+    pub key: String,
+    pub message: String,
+}
+
+/// Checks the workspace against the version policy required by `cargo emanate`:
+/// - member crates inherit the workspace version (`version.workspace = true`)
+///   unless they are versioned independently
+/// - member crates are listed in `workspace.dependencies` with a fixed version
+///   matching the version of the crate
+/// - external `workspace.dependencies` use fixed versions
+pub struct Auditor {
+    ctx: Context,
+}
+
+impl Auditor {
+    pub fn new(ctx: Context) -> Self {
+        Self { ctx }
+    }
+
+    pub async fn audit(&self) -> Result<()> {
+        let Context::Workspace(ctx) = &self.ctx else {
+            return Err("audit is only supported in a workspace".into());
+        };
+
+        let mut violations = vec![];
+        let mut violation = |file: &PathBuf, key: String, message: String| {
+            violations.push(Violation {
+                file: file.clone(),
+                key,
+                message,
+            })
+        };
+
+        for crt in ctx.crates.iter() {
+            let name = crt.name();
+
+            let independent = crt.is_independent()?;
+            match (crt.package.explicit_version(), independent) {
+                (Ok(Some(_)), false) => violation(
+                    &crt.file,
+                    "package.version".to_string(),
+                    "must inherit the workspace version using `version.workspace = true`"
+                        .to_string(),
+                ),
+                (Ok(None), true) => violation(
+                    &crt.file,
+                    "package.version".to_string(),
+                    "independently versioned crate must specify its own version".to_string(),
+                ),
+                (Err(err), _) => {
+                    violation(&crt.file, "package.version".to_string(), err.to_string())
+                }
+                _ => {}
+            }
+
+            let key = format!("workspace.dependencies.{name}");
+            match ctx.manifest.workspace.dependencies.get(name) {
+                None => violation(
+                    &ctx.file,
+                    key,
+                    "workspace member is not listed in `workspace.dependencies`".to_string(),
+                ),
+                Some(dependency) => match (dependency.version(), ctx.crate_version(crt)) {
+                    (Ok(listed), Ok(version)) if listed != version => violation(
+                        &ctx.file,
+                        key,
+                        format!("version `{listed}` does not match crate version `{version}`"),
+                    ),
+                    (Err(Error::RelativeCrate), _) => violation(
+                        &ctx.file,
+                        key.clone(),
+                        format!("`{key}` must specify a fixed version"),
+                    ),
+                    (Err(err), _) => violation(&ctx.file, key, err.to_string()),
+                    _ => {}
+                },
+            }
+        }
+
+        let mut names = ctx.external.keys().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            let dependency = &ctx.external[name];
+            if dependency.git().is_some() {
+                continue;
+            }
+            let key = format!("workspace.dependencies.{name}");
+            match dependency.version() {
+                Ok(_) | Err(Error::RelativeCrate) => {}
+                Err(err) => violation(&ctx.file, key, err.to_string()),
+            }
+        }
+
+        if violations.is_empty() {
+            log_info!(
+                "Audit",
+                "no violations found in {} crates",
+                ctx.crates.len()
+            );
+            return Ok(());
+        }
+
+        for Violation { file, key, message } in violations.iter() {
+            log_error!("Violation", "{} `{key}`: {message}", file.display());
+        }
+
+        Err(error!(
+            "{} version policy violations found",
+            violations.len()
+        ))
+    }
+}
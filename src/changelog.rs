@@ -0,0 +1,260 @@
+use crate::prelude::*;
+
+const HEADER: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/),
+and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).
+";
+
+/// Keep a Changelog sections in the order of their appearance.
+const CATEGORIES: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// Generates "Keep a Changelog" sections from the git commits made since
+/// the last release tag and prepends them to the workspace and/or crate changelogs.
+pub struct Changelog {
+    ctx: Context,
+    config: ChangelogMetadata,
+    dry_run: bool,
+}
+
+impl Changelog {
+    pub fn new(ctx: Context, config: ChangelogMetadata, dry_run: bool) -> Self {
+        Self {
+            ctx,
+            config,
+            dry_run,
+        }
+    }
+
+    /// Updates the changelogs, returning the list of modified files.
+    pub fn update(&self, bumps: &[Bump]) -> Result<Vec<PathBuf>> {
+        let git = Git::open(self.ctx.folder())?;
+        let tags = self.ctx.git()?;
+        let tag = git.last_tag(&tags.tag_pattern(None))?;
+        let mut files = vec![];
+        let file = self.config.file.as_deref().unwrap_or("CHANGELOG.md");
+        let date = today();
+
+        match &self.ctx {
+            Context::Workspace(ctx) => {
+                let workspace_bump = bumps.iter().find(|bump| bump.name.is_none());
+
+                let mut crates = vec![];
+                for crt in ctx.crates.iter() {
+                    let commits = git.commits(tag.as_deref(), Some(&crt.folder))?;
+                    crates.push((crt, commits));
+                }
+
+                if self.config.workspace.unwrap_or(true) {
+                    let title = match workspace_bump {
+                        Some(bump) => bump.to.to_string(),
+                        None => bumps
+                            .iter()
+                            .map(|bump| {
+                                format!("{} {}", bump.name.as_deref().unwrap_or(""), bump.to)
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    };
+
+                    let commits = git.commits(tag.as_deref(), None)?;
+                    let entries = commits
+                        .iter()
+                        .map(|commit| {
+                            let names = crates
+                                .iter()
+                                .filter(|(_, commits)| {
+                                    commits.iter().any(|c| c.hash == commit.hash)
+                                })
+                                .map(|(crt, _)| crt.name().to_string())
+                                .collect::<Vec<_>>();
+                            (commit, names)
+                        })
+                        .collect::<Vec<_>>();
+
+                    if let Some(section) = render(&title, &date, &entries) {
+                        files.push(self.prepend(&ctx.folder.join(file), &section)?);
+                    }
+                }
+
+                if self.config.crates.unwrap_or(false) {
+                    for (crt, commits) in crates.iter() {
+                        let independent = crt.is_independent()?;
+                        let version = if independent {
+                            bumps
+                                .iter()
+                                .find(|bump| bump.name.as_deref() == Some(crt.name()))
+                        } else {
+                            workspace_bump
+                        };
+                        let Some(bump) = version else {
+                            continue;
+                        };
+
+                        // independent crates are released under their own tags
+                        let own_commits;
+                        let commits = if independent {
+                            let tag = git.last_tag(&tags.tag_pattern(Some(crt.name())))?;
+                            own_commits = git.commits(tag.as_deref(), Some(&crt.folder))?;
+                            &own_commits
+                        } else {
+                            commits
+                        };
+
+                        let entries = commits
+                            .iter()
+                            .map(|commit| (commit, vec![]))
+                            .collect::<Vec<_>>();
+                        if let Some(section) = render(&bump.to.to_string(), &date, &entries) {
+                            files.push(self.prepend(&crt.folder.join(file), &section)?);
+                        }
+                    }
+                }
+            }
+            Context::Crate(ctx) => {
+                let Some(bump) = bumps.first() else {
+                    return Ok(files);
+                };
+                let commits = git.commits(tag.as_deref(), Some(&ctx.folder))?;
+                let entries = commits
+                    .iter()
+                    .map(|commit| (commit, vec![]))
+                    .collect::<Vec<_>>();
+                if let Some(section) = render(&bump.to.to_string(), &date, &entries) {
+                    files.push(self.prepend(&ctx.folder.join(file), &section)?);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Inserts the section above the most recent release section,
+    /// keeping the header and the `[Unreleased]` section on top.
+    fn prepend(&self, file: &Path, section: &str) -> Result<PathBuf> {
+        if self.dry_run {
+            log_info!("Changelog", "`{}`:", file.display());
+            println!("\n{section}");
+            return Ok(file.to_path_buf());
+        }
+
+        let changelog = if file.exists() {
+            let existing = fs::read_to_string(file)?;
+            let position = existing
+                .match_indices("\n## ")
+                .map(|(idx, _)| idx + 1)
+                .find(|idx| !existing[*idx..].starts_with("## [Unreleased]"));
+            match position {
+                Some(idx) => format!("{}{section}\n{}", &existing[..idx], &existing[idx..]),
+                None => format!("{}\n\n{section}", existing.trim_end()),
+            }
+        } else {
+            format!("{HEADER}\n{section}")
+        };
+
+        fs::write(file, changelog)?;
+        log_info!("Changelog", "updated `{}`", file.display());
+
+        Ok(file.to_path_buf())
+    }
+}
+
+/// Keep a Changelog category of the commit, `None` if the commit
+/// should not be listed (i.e. `chore`, `ci`, `docs`, `test`).
+fn category(commit: &Commit) -> Option<&'static str> {
+    match commit.kind.as_deref() {
+        Some("feat") => Some("Added"),
+        Some("fix") => Some("Fixed"),
+        Some("deprecate") => Some("Deprecated"),
+        Some("remove") => Some("Removed"),
+        Some("security") => Some("Security"),
+        Some("perf" | "refactor") | None => Some("Changed"),
+        _ if commit.breaking => Some("Changed"),
+        _ => None,
+    }
+}
+
+/// Renders a changelog section, grouping commits by category and,
+/// for commits annotated with crate names, by the affected crate.
+fn render(title: &str, date: &str, entries: &[(&Commit, Vec<String>)]) -> Option<String> {
+    let mut section = String::new();
+
+    for name in CATEGORIES {
+        let commits = entries
+            .iter()
+            .filter(|(commit, _)| category(commit) == Some(name))
+            .collect::<Vec<_>>();
+        if commits.is_empty() {
+            continue;
+        }
+
+        section.push_str(&format!("\n### {name}\n"));
+
+        let mut groups = commits
+            .iter()
+            .flat_map(|(_, names)| names.iter().map(String::as_str))
+            .collect::<Vec<_>>();
+        groups.sort();
+        groups.dedup();
+
+        if groups.is_empty() {
+            section.push('\n');
+            for (commit, _) in commits {
+                section.push_str(&entry(commit));
+            }
+        } else {
+            let common = commits
+                .iter()
+                .filter(|(_, names)| names.is_empty())
+                .collect::<Vec<_>>();
+            if !common.is_empty() {
+                section.push_str("\n#### workspace\n\n");
+                for (commit, _) in common {
+                    section.push_str(&entry(commit));
+                }
+            }
+            for group in groups {
+                section.push_str(&format!("\n#### {group}\n\n"));
+                for (commit, _) in commits
+                    .iter()
+                    .filter(|(_, names)| names.iter().any(|n| n == group))
+                {
+                    section.push_str(&entry(commit));
+                }
+            }
+        }
+    }
+
+    if section.is_empty() {
+        None
+    } else {
+        Some(format!("## [{title}] - {date}\n{section}"))
+    }
+}
+
+fn entry(commit: &Commit) -> String {
+    let breaking = if commit.breaking {
+        "**BREAKING:** "
+    } else {
+        ""
+    };
+    let scope = commit
+        .scope
+        .as_ref()
+        .map(|scope| format!("**{scope}:** "))
+        .unwrap_or_default();
+    format!(
+        "- {breaking}{scope}{} ({})\n",
+        commit.description,
+        commit.short_hash()
+    )
+}
@@ -1,48 +1,208 @@
 use crate::prelude::*;
+use serde::Serialize;
+
+/// Classification of a dependency version against the latest
+/// version, following cargo's caret compatibility rules. Versions
+/// ahead of the latest (e.g. a pre-release or a version newer than
+/// a yanked latest) are considered up to date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DependencyStatus {
+    /// up to date
+    Ok,
+    /// compatible update within the same minor version (`0.y` for `0.y.z`)
+    Patch,
+    /// compatible update to a newer minor version
+    Minor,
+    /// incompatible update (a new major version or `0.x` -> `0.y`)
+    Breaking,
+    /// declared by a member crate using a version other than `x.y.z`
+    NotFixed,
+    /// crate not found in the registry or without released versions
+    Unknown,
+}
+
+impl DependencyStatus {
+    pub fn of(version: &Version, latest: &Version) -> DependencyStatus {
+        if version >= latest {
+            DependencyStatus::Ok
+        } else if !version.is_compatible(latest) {
+            DependencyStatus::Breaking
+        } else if version.major == latest.major && version.minor == latest.minor {
+            DependencyStatus::Patch
+        } else {
+            DependencyStatus::Minor
+        }
+    }
+}
+
+impl std::fmt::Display for DependencyStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DependencyStatus::Ok => write!(f, "ok"),
+            DependencyStatus::Patch => write!(f, "patch"),
+            DependencyStatus::Minor => write!(f, "minor"),
+            DependencyStatus::Breaking => write!(f, "breaking"),
+            DependencyStatus::NotFixed => write!(f, "not fixed"),
+            DependencyStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Output format of `check`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// coloured console output
+    #[default]
+    Table,
+    /// JSON array of dependencies
+    Json,
+    /// Markdown table ready to be pasted into an issue
+    Markdown,
+}
+
+/// Results of `check` causing a non-zero exit status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FailOn {
+    /// any dependency is not at its latest version
+    Outdated,
+    /// any dependency has a breaking update available
+    Breaking,
+    /// any dependency version has been yanked
+    Yanked,
+}
+
+/// Result of a dependency check for a single declaration of the dependency.
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyReport {
+    pub name: String,
+    /// declared version
+    pub version: String,
+    /// latest release, `None` if the crate has no released versions
+    pub latest: Option<String>,
+    pub latest_compatible: Option<String>,
+    pub status: DependencyStatus,
+    /// manifest declaring the dependency, relative to the workspace folder
+    pub file: String,
+    /// `true` if the declared version has been yanked
+    pub yanked: bool,
+}
 
 /// Checks for the latest version of the crate
 #[derive(Debug)]
 pub struct Checker {
     ctx: Context,
+    /// check only dependencies from the registry requested using `--registry`
+    registry: Option<String>,
 }
 
 impl Checker {
-    pub fn new(ctx: Context) -> Checker {
-        Checker { ctx }
+    pub fn new(ctx: Context, registry: Option<String>) -> Checker {
+        Checker { ctx, registry }
     }
 
-    pub async fn check(&self) -> Result<()> {
-        let deps = self.ctx.dependencies();
+    /// Client of the registry the dependency is published to.
+    fn client<'a>(
+        &self,
+        clients: &'a mut HashMap<Option<String>, CratesIo>,
+        dep: &Dependency,
+    ) -> Result<&'a CratesIo> {
+        let name = dep.registry().map(str::to_string);
+        if !clients.contains_key(&name) {
+            let registry = Registry::resolve(self.ctx.folder(), name.as_deref())?;
+            clients.insert(name.clone(), CratesIo::with_registry(&registry)?);
+        }
+        Ok(&clients[&name])
+    }
+
+    /// Reports of a dependency declaration, one per declaring manifest.
+    fn reports(
+        &self,
+        report: DependencyReport,
+        crates: &[Option<&Crate>],
+    ) -> Vec<DependencyReport> {
+        crates
+            .iter()
+            .map(|crt| {
+                let file = crt.map(|crt| &crt.file).unwrap_or(self.ctx.file());
+                DependencyReport {
+                    file: file
+                        .strip_prefix(self.ctx.folder())
+                        .unwrap_or(file)
+                        .display()
+                        .to_string(),
+                    ..report.clone()
+                }
+            })
+            .collect()
+    }
 
-        let client = CratesIo::new();
-        let mut names = deps.keys().collect::<Vec<_>>();
-        names.sort();
+    /// Checks dependencies against their latest versions. If `update` is
+    /// specified, the given dependencies (all if empty) are updated to
+    /// their latest (or latest compatible if `compatible_only`) versions.
+    /// Fails if a dependency matches the `fail_on` condition.
+    pub async fn check(
+        &self,
+        update: Option<Vec<String>>,
+        compatible_only: bool,
+        format: Format,
+        fail_on: Option<FailOn>,
+        dry_run: bool,
+    ) -> Result<()> {
+        if update.is_some() && format != Format::Table {
+            return Err("`--update` is only supported with the table format".into());
+        }
+
+        // dependencies are identified by the crate name, taking `package` renames
+        // into account, as the registries and `update_dependencies` do
+        let mut deps = self
+            .ctx
+            .external_dependencies()
+            .into_iter()
+            .map(|(name, crt, dep)| (dep.package().unwrap_or(name), crt, dep))
+            .collect::<Vec<_>>();
+        deps.retain(|(_, crt, dep)| is_checked(*crt, dep));
+        if let Some(registry) = self.registry.as_deref() {
+            deps.retain(|(_, _, dep)| dep.registry().unwrap_or("crates-io") == registry);
+        }
+        deps.sort_by_key(|(name, crt, _)| (*name, crt.map(Crate::name)));
+
+        let mut names = deps.iter().map(|(name, _, _)| *name).collect::<Vec<_>>();
+        names.dedup();
+        if let Some(update) = update.as_ref() {
+            for name in update {
+                if !names.contains(&name.as_str()) {
+                    return Err(error!("dependency `{name}` not found"));
+                }
+            }
+        }
+
+        let mut clients = HashMap::new();
         let len = names.iter().map(|c| c.len()).fold(0, |a, b| a.max(b)) + 2;
 
-        // pre-check versions
-        for name in names.iter() {
-            let dep = deps.get(*name).unwrap();
+        // pre-check versions, member crates declaring versions other
+        // than `x.y.z` are reported along with the other dependencies
+        for (name, _, dep) in deps.iter().filter(|(_, crt, _)| crt.is_none()) {
             if dep.git().is_some() {
                 continue;
             }
             match dep.version() {
                 Err(Error::WorkspaceCrate) => {
-                    println!(
-                        "{} `{}`",
-                        style("this appears to be a workspace crate:").red(),
-                        style(self.ctx.file().display()).yellow()
-                    );
-                    return Ok(());
-                }
-                Err(Error::RelativeCrate) => {
-                    println!("`{name}`: relative crate, ignoring...");
+                    return Err(error!(
+                        "`{name}` is inherited from the workspace, `{}` appears to be a workspace crate",
+                        self.ctx.file().display()
+                    ));
                 }
                 Err(err) => {
-                    println!("`{name}`: {err}");
-                    let latest_version = client.get_latest_version(name).await?;
-                    println!("latest version for `{name}` is: `{latest_version}`");
-                    println!("aborting...");
-                    return Ok(());
+                    let latest = match self
+                        .client(&mut clients, dep)?
+                        .get_latest_version(name)
+                        .await
+                    {
+                        Ok(latest) => format!(", latest version is `{latest}`"),
+                        Err(_) => String::new(),
+                    };
+                    return Err(error!("`{name}`: {err}{latest}"));
                 }
                 _ => {}
             }
@@ -53,36 +213,424 @@ impl Checker {
             // .map_err(|err| error!("Error processing dependency `{name}`: {err}"))?;
         }
 
+        // query the registries concurrently, grouping dependencies by registry
+        let mut registries: Vec<(Option<String>, Vec<&str>)> = vec![];
+        for name in names.iter() {
+            let Some((_, _, dep)) = deps.iter().find(|(n, _, _)| n == name) else {
+                continue;
+            };
+            self.client(&mut clients, dep)?;
+            let registry = dep.registry().map(str::to_string);
+            match registries.iter_mut().find(|(r, _)| *r == registry) {
+                Some((_, names)) => names.push(name),
+                None => registries.push((registry, vec![name])),
+            }
+        }
+        let mut results = HashMap::new();
+        for (registry, names) in registries.iter() {
+            let crates = clients[registry].get_crates(names).await;
+            results.extend(names.iter().copied().zip(crates));
+        }
+
         // check against crates.io
+        let mut updates = vec![];
+        let mut summary = HashMap::new();
+        let mut mismatches = vec![];
+        let mut reports = vec![];
         for name in names.iter() {
-            let dependency = deps.get(*name).unwrap();
-            let version = match dependency.version() {
-                Ok(v) => v,
-                Err(_) => continue,
+            // versions of the dependency along with the crates declaring them
+            let mut versions: Vec<(Version, Vec<Option<&Crate>>)> = vec![];
+            // member declarations using versions other than `x.y.z`
+            let mut requirements: Vec<(String, Vec<Option<&Crate>>)> = vec![];
+            for (_, crt, dep) in deps.iter().filter(|(n, _, _)| n == name) {
+                match dep.version() {
+                    Ok(version) => match versions.iter_mut().find(|(v, _)| *v == version) {
+                        Some((_, crates)) => crates.push(*crt),
+                        None => versions.push((version, vec![*crt])),
+                    },
+                    Err(_) => {
+                        let requirement = dep.requirement().unwrap_or("*").to_string();
+                        match requirements.iter_mut().find(|(r, _)| *r == requirement) {
+                            Some((_, crates)) => crates.push(*crt),
+                            None => requirements.push((requirement, vec![*crt])),
+                        }
+                    }
+                }
+            }
+            versions.sort_by(|(a, _), (b, _)| a.cmp(b));
+            if versions.len() > 1 {
+                let pinned = versions
+                    .iter()
+                    .map(|(version, crates)| format!("{version} ({})", declared_by(crates)))
+                    .collect::<Vec<_>>();
+                mismatches.push(format!("{name} is pinned at {}", pinned.join(", ")));
+            }
+
+            let published = results.remove(name).unwrap_or(Ok(None))?;
+            let latest = published.as_ref().and_then(RegistryCrate::latest);
+            let row = name.pad(len, ' ', Alignment::Right, false);
+
+            for (requirement, crates) in requirements {
+                *summary.entry(DependencyStatus::NotFixed).or_insert(0) += 1;
+                reports.extend(self.reports(
+                    DependencyReport {
+                        name: name.to_string(),
+                        version: requirement.clone(),
+                        latest: latest.as_ref().map(Version::to_string),
+                        latest_compatible: None,
+                        status: DependencyStatus::NotFixed,
+                        file: String::new(),
+                        yanked: false,
+                    },
+                    &crates,
+                ));
+                if format == Format::Table {
+                    let latest = latest
+                        .as_ref()
+                        .map(|latest| format!(" (latest: {latest})"))
+                        .unwrap_or_default();
+                    println!(
+                        "{}",
+                        style(format!(
+                            "{row:>4} {requirement} - not a fixed version{latest} [{}]",
+                            declared_by(&crates)
+                        ))
+                        .red()
+                    );
+                }
+            }
+
+            let (Some(published), Some(latest_version)) = (published, latest) else {
+                for (version, crates) in versions {
+                    *summary.entry(DependencyStatus::Unknown).or_insert(0) += 1;
+                    reports.extend(self.reports(
+                        DependencyReport {
+                            name: name.to_string(),
+                            version: version.to_string(),
+                            latest: None,
+                            latest_compatible: None,
+                            status: DependencyStatus::Unknown,
+                            file: String::new(),
+                            yanked: false,
+                        },
+                        &crates,
+                    ));
+                    if format == Format::Table {
+                        let members = if crates.iter().any(Option::is_some) {
+                            format!(" [{}]", declared_by(&crates))
+                        } else {
+                            String::new()
+                        };
+                        println!(
+                            "{}",
+                            style(format!("{row:>4} {version} - not found{members}")).red()
+                        );
+                    }
+                }
+                continue;
             };
-            // .map_err(|err| error!("Error processing dependency `{name}`: {err}"))?;
-            let latest_version = client.get_latest_version(name).await?;
-            if version != latest_version {
-                println!(
-                    "{}",
-                    style(format!(
-                        "{:>4} {version} -> {latest_version} - update",
-                        name.pad(len, ' ', Alignment::Right, false)
-                    ))
-                    .yellow()
-                );
+
+            for (version, crates) in versions {
+                let selected = update
+                    .as_ref()
+                    .is_some_and(|update| update.is_empty() || update.iter().any(|u| u == name));
+                if selected {
+                    let target = if compatible_only {
+                        published.latest_compatible(&version)
+                    } else {
+                        Some(latest_version.clone())
+                    };
+                    match target {
+                        Some(target) if target > version => {
+                            for crt in crates.iter() {
+                                let file = crt.map(|crt| &crt.file);
+                                updates.push((file, *name, version.clone(), target.clone()));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+
+                // member crates declaring the dependency
+                let members = if crates.iter().any(Option::is_some) {
+                    format!(" [{}]", declared_by(&crates))
+                } else {
+                    String::new()
+                };
+
+                let status = DependencyStatus::of(&version, &latest_version);
+                *summary.entry(status).or_insert(0) += 1;
+                let latest_compatible = published.latest_compatible(&version);
+                let yanked = published
+                    .versions
+                    .iter()
+                    .any(|v| v.version == version && v.yanked);
+                reports.extend(self.reports(
+                    DependencyReport {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                        latest: Some(latest_version.to_string()),
+                        latest_compatible: latest_compatible.as_ref().map(Version::to_string),
+                        status,
+                        file: String::new(),
+                        yanked,
+                    },
+                    &crates,
+                ));
+                if format != Format::Table {
+                    continue;
+                }
+
+                let members = if yanked {
+                    format!(" - {}{members}", style("yanked").red().bold())
+                } else {
+                    members
+                };
+                match status {
+                    DependencyStatus::Ok => println!(
+                        "{}",
+                        style(format!(
+                            "{row:>4} {version} -- {latest_version} - ok{members}"
+                        ))
+                        .green()
+                    ),
+                    status => {
+                        // the newest compatible version, if other than the newest overall
+                        let compatible = latest_compatible
+                            .filter(|compatible| {
+                                *compatible > version && *compatible != latest_version
+                            })
+                            .map(|compatible| format!(" (compatible: {compatible})"))
+                            .unwrap_or_default();
+                        let row = format!(
+                            "{row:>4} {version} -> {latest_version} - {status}{compatible}{members}"
+                        );
+                        match status {
+                            DependencyStatus::Breaking => println!("{}", style(row).red()),
+                            DependencyStatus::Minor => println!("{}", style(row).yellow()),
+                            _ => println!("{}", style(row).cyan()),
+                        }
+                    }
+                }
+            }
+        }
+
+        let yanked = reports.iter().filter(|report| report.yanked).count();
+        // findings are only listed if present
+        let findings = [DependencyStatus::NotFixed, DependencyStatus::Unknown]
+            .iter()
+            .filter_map(|status| Some(format!("{} {status}", summary.get(status)?)))
+            .collect::<Vec<_>>();
+        let mut summary = [
+            DependencyStatus::Ok,
+            DependencyStatus::Patch,
+            DependencyStatus::Minor,
+            DependencyStatus::Breaking,
+        ]
+        .iter()
+        .map(|status| format!("{} {status}", summary.get(status).unwrap_or(&0)))
+        .collect::<Vec<_>>();
+        summary.extend(findings);
+        if yanked > 0 {
+            summary.push(format!("{yanked} yanked"));
+        }
+
+        match format {
+            Format::Table => {
+                for mismatch in mismatches.iter() {
+                    log_warn!("Mismatch", "{mismatch}");
+                }
+                log_info!("Summary", "{}", summary.join(", "));
+            }
+            Format::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+            Format::Markdown => println!("{}", markdown(&reports, &mismatches, &summary)),
+        }
+
+        if update.is_some() {
+            self.update(&updates, dry_run)?;
+        }
+
+        let failures = match fail_on {
+            None => 0,
+            Some(FailOn::Outdated) => reports
+                .iter()
+                .filter(|report| {
+                    matches!(
+                        report.status,
+                        DependencyStatus::Patch
+                            | DependencyStatus::Minor
+                            | DependencyStatus::Breaking
+                    )
+                })
+                .count(),
+            Some(FailOn::Breaking) => reports
+                .iter()
+                .filter(|report| report.status == DependencyStatus::Breaking)
+                .count(),
+            Some(FailOn::Yanked) => yanked,
+        };
+        if failures > 0 {
+            return Err(match fail_on {
+                Some(FailOn::Outdated) => error!("{failures} outdated dependencies found"),
+                Some(FailOn::Breaking) => {
+                    error!("{failures} dependencies with breaking updates found")
+                }
+                _ => error!("{failures} yanked dependency versions found"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the versions of the dependencies in the manifests of the member
+    /// crates declaring them and in `workspace.dependencies` (or the standalone
+    /// crate dependencies) if no member file is given, preserving the formatting.
+    fn update(
+        &self,
+        updates: &[(Option<&PathBuf>, &str, Version, Version)],
+        dry_run: bool,
+    ) -> Result<()> {
+        if updates.is_empty() {
+            log_info!("Update", "all dependencies are up to date");
+            return Ok(());
+        }
+
+        let mut files = vec![];
+        for (file, _, _, _) in updates.iter() {
+            if !files.contains(file) {
+                files.push(*file);
+            }
+        }
+
+        for member in files {
+            let file = member.unwrap_or(self.ctx.file());
+            let mut doc = load_document(file)?;
+            for (_, name, version, target) in updates.iter().filter(|(f, _, _, _)| *f == member) {
+                let target = target.to_string();
+                let count = match (&self.ctx, member) {
+                    (Context::Workspace(_), None) => doc["workspace"]
+                        .get_mut("dependencies")
+                        .and_then(toml_edit::Item::as_table_like_mut)
+                        .map(|deps| update_dependencies(deps, name, &target))
+                        .unwrap_or_default(),
+                    _ => update_dependency_tables(doc.as_table_mut(), name, &target),
+                };
+                if count > 0 {
+                    log_info!("Update", "{name} {version} -> {target}");
+                } else {
+                    log_warn!("Update", "{name} - unable to locate the dependency version");
+                }
+            }
+
+            if dry_run {
+                log_info!("Dry-run", "skipping update of `{}`", file.display());
             } else {
-                println!(
-                    "{}",
-                    style(format!(
-                        "{:>4} {version} -- {latest_version} - ok",
-                        name.pad(len, ' ', Alignment::Right, false)
-                    ))
-                    .green()
-                );
+                fs::write(file, doc.to_string())?;
+                log_info!("Update", "`{}` updated", file.display());
             }
         }
 
         Ok(())
     }
 }
+
+/// Returns `true` if the dependency declared by the given member crate (or
+/// in `workspace.dependencies` if `None`) is checked against the registry.
+/// Git and path dependencies are not published to a registry unless they
+/// specify a version; those of members (e.g. unpublished test helpers) are
+/// not checked at all. Dependencies inherited by members are checked
+/// with `workspace.dependencies`.
+fn is_checked(crt: Option<&Crate>, dep: &Dependency) -> bool {
+    match crt {
+        None => (dep.git().is_none() && !dep.is_path()) || dep.has_version(),
+        Some(_) => dep.git().is_none() && !dep.is_path() && !dep.is_workspace(),
+    }
+}
+
+/// Names of the crates declaring the dependency,
+/// `workspace` for `workspace.dependencies`.
+fn declared_by(crates: &[Option<&Crate>]) -> String {
+    crates
+        .iter()
+        .map(|crt| crt.map(Crate::name).unwrap_or("workspace"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Markdown report listing each dependency declaration,
+/// the mismatching versions and the summary.
+fn markdown(reports: &[DependencyReport], mismatches: &[String], summary: &[String]) -> String {
+    let mut lines = vec![
+        "| Dependency | Version | Latest | Compatible | Status | Yanked | Declared in |"
+            .to_string(),
+        "|---|---|---|---|---|---|---|".to_string(),
+    ];
+    for report in reports {
+        lines.push(format!(
+            "| `{}` | {} | {} | {} | {} | {} | `{}` |",
+            report.name,
+            report.version,
+            report.latest.as_deref().unwrap_or("-"),
+            report.latest_compatible.as_deref().unwrap_or("-"),
+            report.status,
+            if report.yanked { "yes" } else { "" },
+            report.file
+        ));
+    }
+    if !mismatches.is_empty() {
+        lines.push(String::new());
+        lines.push("**Mismatches**".to_string());
+        lines.push(String::new());
+        lines.extend(mismatches.iter().map(|mismatch| format!("- {mismatch}")));
+    }
+    lines.push(String::new());
+    lines.push(format!("**Summary:** {}", summary.join(", ")));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(version: &str, latest: &str) -> DependencyStatus {
+        DependencyStatus::of(&version.parse().unwrap(), &latest.parse().unwrap())
+    }
+
+    fn dependency(toml: &str) -> Dependency {
+        toml::from_str::<toml::Value>(&format!("dep = {toml}")).unwrap()["dep"]
+            .clone()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn checked_dependencies() {
+        let member =
+            toml::from_str::<Crate>("[package]\nname = \"app\"\nversion = \"0.1.0\"").unwrap();
+        let git = dependency(r#"{ git = "https://github.com/aspectron/mygit" }"#);
+        let path = dependency(r#"{ path = "../local" }"#);
+        let helper = dependency(r#"{ path = "../helper", version = "0.1.0" }"#);
+        let inherited = dependency("{ workspace = true }");
+        let registry = dependency(r#""1.0.0""#);
+        for crt in [None, Some(&member)] {
+            assert!(!is_checked(crt, &git));
+            assert!(!is_checked(crt, &path));
+            assert!(is_checked(crt, &registry));
+        }
+        assert!(is_checked(None, &helper));
+        assert!(!is_checked(Some(&member), &helper));
+        assert!(!is_checked(Some(&member), &inherited));
+    }
+
+    #[test]
+    fn dependency_status() {
+        assert_eq!(status("1.2.3", "1.2.3"), DependencyStatus::Ok);
+        assert_eq!(status("1.2.3", "1.2.5"), DependencyStatus::Patch);
+        assert_eq!(status("0.4.1", "0.4.9"), DependencyStatus::Patch);
+        assert_eq!(status("1.2.3", "1.4.0"), DependencyStatus::Minor);
+        assert_eq!(status("1.2.3", "2.0.0"), DependencyStatus::Breaking);
+        assert_eq!(status("0.4.1", "0.5.0"), DependencyStatus::Breaking);
+        assert_eq!(status("1.5.0-beta.1", "1.4.0"), DependencyStatus::Ok);
+        assert_eq!(status("2.0.0", "1.4.0"), DependencyStatus::Ok);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::prelude::*;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Git commit following the Conventional Commits specification:
+/// <https://www.conventionalcommits.org/en/v1.0.0/>
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub hash: String,
+    pub subject: String,
+    /// commit type (`feat`, `fix`, ...), `None` for non-conventional commits
+    pub kind: Option<String>,
+    pub scope: Option<String>,
+    /// `!` after the type/scope or a `BREAKING CHANGE:` footer
+    pub breaking: bool,
+    /// subject without the type and scope prefix
+    pub description: String,
+}
+
+/// Conventional Commits subject and breaking change footer patterns,
+/// compiled once for all parsed commits
+static SUBJECT: OnceLock<Regex> = OnceLock::new();
+static FOOTER: OnceLock<Regex> = OnceLock::new();
+
+impl Commit {
+    pub fn parse(hash: &str, subject: &str, body: &str) -> Commit {
+        let re = SUBJECT.get_or_init(|| {
+            Regex::new(
+                r"^(?P<kind>[A-Za-z]+)(?:\((?P<scope>[^)]*)\))?(?P<breaking>!)?: (?P<description>.+)$",
+            )
+            .unwrap()
+        });
+        let footer = FOOTER.get_or_init(|| Regex::new(r"(?m)^BREAKING[ -]CHANGE: ").unwrap());
+
+        let mut commit = Commit {
+            hash: hash.to_string(),
+            subject: subject.to_string(),
+            kind: None,
+            scope: None,
+            breaking: false,
+            description: subject.to_string(),
+        };
+
+        if let Some(captures) = re.captures(subject) {
+            commit.kind = Some(captures["kind"].to_lowercase());
+            commit.scope = captures.name("scope").map(|m| m.as_str().to_string());
+            commit.breaking = captures.name("breaking").is_some() || footer.is_match(body);
+            commit.description = captures["description"].to_string();
+        }
+
+        commit
+    }
+
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+
+    /// Version change warranted by this commit: `major` for breaking changes
+    /// (`minor` prior to 1.0.0), `minor` for features and `patch` for fixes.
+    pub fn change(&self, version: &Version) -> Option<Change> {
+        if self.breaking {
+            if version.major == 0 {
+                Some(Change::Minor)
+            } else {
+                Some(Change::Major)
+            }
+        } else {
+            match self.kind.as_deref() {
+                Some("feat") => Some(Change::Minor),
+                Some("fix") => Some(Change::Patch),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Infers the version change from the commits made since the last release,
+/// logging the commits that led to the decision.
+pub fn infer_change(commits: &[Commit], version: &Version) -> Result<Change> {
+    let rank = |change: &Change| match change {
+        Change::Major => 3,
+        Change::Minor => 2,
+        _ => 1,
+    };
+
+    let changes = commits
+        .iter()
+        .filter_map(|commit| commit.change(version).map(|change| (commit, change)))
+        .collect::<Vec<_>>();
+
+    let Some(change) = changes
+        .iter()
+        .map(|(_, change)| change)
+        .max_by_key(|change| rank(change))
+        .cloned()
+    else {
+        return Err(error!(
+            "no `feat`, `fix` or breaking commits found among {} commits since the last release",
+            commits.len()
+        ));
+    };
+
+    for (commit, _) in changes.iter().filter(|(_, c)| rank(c) == rank(&change)) {
+        log_info!("Commit", "{} {}", commit.short_hash(), commit.subject);
+    }
+    log_info!("Auto", "{change} change based on {} commits", commits.len());
+
+    Ok(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inferred(subjects: &[(&str, &str)], version: &str) -> Result<String> {
+        let commits = subjects
+            .iter()
+            .map(|(subject, body)| Commit::parse("0123456789abcdef", subject, body))
+            .collect::<Vec<_>>();
+        Ok(infer_change(&commits, &version.parse()?)?.to_string())
+    }
+
+    #[test]
+    fn parse_conventional_commits() {
+        let commit = Commit::parse("0123456789abcdef", "feat(cli): add yank", "");
+        assert_eq!(commit.kind.as_deref(), Some("feat"));
+        assert_eq!(commit.scope.as_deref(), Some("cli"));
+        assert_eq!(commit.description, "add yank");
+        assert!(!commit.breaking);
+        assert_eq!(commit.short_hash(), "0123456");
+
+        let commit = Commit::parse("0123456789abcdef", "Fix!: drop crates_io_api", "");
+        assert_eq!(commit.kind.as_deref(), Some("fix"));
+        assert!(commit.breaking);
+
+        let commit = Commit::parse(
+            "0123456789abcdef",
+            "refactor: registry client",
+            "Details.\n\nBREAKING CHANGE: `Registry::new` removed",
+        );
+        assert!(commit.breaking);
+
+        let commit = Commit::parse("0123456789abcdef", "Update README", "");
+        assert_eq!(commit.kind, None);
+        assert_eq!(commit.description, "Update README");
+        assert!(!commit.breaking);
+    }
+
+    #[test]
+    fn infer_version_change() {
+        assert_eq!(inferred(&[("fix: a", "")], "1.2.3").unwrap(), "patch");
+        assert_eq!(
+            inferred(&[("fix: a", ""), ("feat: b", "")], "1.2.3").unwrap(),
+            "minor"
+        );
+        assert_eq!(
+            inferred(&[("feat!: b", ""), ("fix: a", "")], "1.2.3").unwrap(),
+            "major"
+        );
+        assert_eq!(
+            inferred(&[("chore: c", "BREAKING CHANGE: d")], "1.2.3").unwrap(),
+            "major"
+        );
+        // breaking changes bump the minor version prior to 1.0.0
+        assert_eq!(inferred(&[("feat!: b", "")], "0.4.1").unwrap(), "minor");
+        assert!(inferred(&[("Update README", ""), ("chore: c", "")], "1.2.3").is_err());
+    }
+}
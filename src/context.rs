@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use futures_util::future::*;
+use std::collections::HashSet;
 use toml::*;
 
 // pub struct Ref {
@@ -27,10 +28,33 @@ impl Context {
         }
     }
 
-    pub fn dependencies(&self) -> &Dependencies {
+    /// External dependencies paired with the member crate declaring them:
+    /// `workspace.dependencies` (declared by no crate) followed by the
+    /// dependencies members declare themselves, or the dependencies
+    /// of a standalone crate.
+    pub fn external_dependencies(&self) -> Vec<(&str, Option<&Crate>, &Dependency)> {
         match self {
-            Context::Workspace(ctx) => &ctx.external,
-            Context::Crate(ctx) => &ctx.dependencies,
+            Context::Workspace(ctx) => {
+                let mut deps = ctx
+                    .external
+                    .iter()
+                    .map(|(name, dependency)| (name.as_str(), None, dependency))
+                    .collect::<Vec<_>>();
+                for crt in ctx.crates.iter() {
+                    deps.extend(
+                        crt.external
+                            .iter()
+                            .map(|(name, dependency)| (name.as_str(), Some(crt), dependency)),
+                    );
+                }
+                deps
+            }
+            Context::Crate(ctx) => ctx
+                .manifest
+                .dependencies
+                .iter()
+                .map(|(name, dependency)| (name.as_str(), None, dependency))
+                .collect(),
         }
     }
 
@@ -40,13 +64,111 @@ impl Context {
             Context::Crate(ctx) => &ctx.file,
         }
     }
+
+    /// Folder commands such as `cargo publish` or `cargo build` are executed in.
+    pub fn folder(&self) -> &PathBuf {
+        match self {
+            Context::Workspace(ctx) => &ctx.folder,
+            Context::Crate(ctx) => &ctx.folder,
+        }
+    }
+
+    /// Published crates in the publishing order; a single crate in the
+    /// context of a standalone crate, none if its publishing is disabled.
+    pub fn crates(&self) -> Vec<&Crate> {
+        match self {
+            Context::Workspace(ctx) => ctx.crates.iter().collect(),
+            Context::Crate(ctx) => ctx.published().into_iter().collect(),
+        }
+    }
+
+    /// Crates grouped into publishing layers, crates within a layer
+    /// depend only on crates from the preceding layers.
+    pub fn layers(&self) -> Vec<Vec<&Crate>> {
+        match self {
+            Context::Workspace(ctx) => ctx.layers(),
+            Context::Crate(ctx) => ctx
+                .published()
+                .map(|crt| vec![vec![crt]])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Changelog configuration of the workspace or the standalone crate.
+    pub fn changelog(&self) -> Result<Option<ChangelogMetadata>> {
+        match self {
+            Context::Workspace(ctx) => Ok(ctx.manifest.metadata()?.and_then(|m| m.changelog)),
+            Context::Crate(ctx) => Ok(ctx.manifest.metadata()?.and_then(|m| m.changelog)),
+        }
+    }
+
+    /// Release commit and tag configuration of the workspace or the standalone crate.
+    pub fn git(&self) -> Result<GitMetadata> {
+        let git = match self {
+            Context::Workspace(ctx) => ctx.manifest.metadata()?.and_then(|m| m.git),
+            Context::Crate(ctx) => ctx.manifest.metadata()?.and_then(|m| m.git),
+        };
+        Ok(git.unwrap_or_default())
+    }
+
+    /// Non-Cargo files containing the version of the workspace or the standalone crate.
+    pub fn version_files(&self) -> Result<Vec<VersionFile>> {
+        let version = match self {
+            Context::Workspace(ctx) => ctx.manifest.metadata()?.and_then(|m| m.version),
+            Context::Crate(ctx) => ctx.manifest.metadata()?.and_then(|m| m.version),
+        };
+        Ok(version.and_then(|v| v.files).unwrap_or_default())
+    }
+
+    /// Publishing configuration of the workspace or the standalone crate.
+    pub fn publish(&self) -> Result<PublishMetadata> {
+        let publish = match self {
+            Context::Workspace(ctx) => ctx.manifest.metadata()?.and_then(|m| m.publish),
+            Context::Crate(ctx) => ctx.manifest.metadata()?.and_then(|m| m.publish),
+        };
+        Ok(publish.unwrap_or_default())
+    }
+
+    /// Registries the crates are published to (see [`Registry::select`]), crates
+    /// that can not be published to the requested registry are omitted.
+    pub fn registries(&self, requested: Option<&str>) -> Result<HashMap<String, Registry>> {
+        let mut registries = HashMap::new();
+        for crt in self.crates() {
+            if let Some(registry) = Registry::select(self.folder(), crt, requested)? {
+                registries.insert(crt.name().to_string(), registry);
+            }
+        }
+        Ok(registries)
+    }
+
+    /// Release version: the workspace version or the version of the crate.
+    pub fn version(&self) -> Result<Version> {
+        match self {
+            Context::Workspace(ctx) => ctx.manifest.version(),
+            Context::Crate(ctx) => self.crate_version(&ctx.manifest),
+        }
+    }
+
+    /// Version of the given crate.
+    pub fn crate_version(&self, crt: &Crate) -> Result<Version> {
+        match self {
+            Context::Workspace(ctx) => ctx.crate_version(crt),
+            Context::Crate(_) => crt
+                .package
+                .explicit_version()?
+                .ok_or_else(|| error!("`{}` is missing `package.version`", crt.name())),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct CrateContext {
+    /// path to the crate `Cargo.toml` file.
     pub file: PathBuf,
-    pub package: Package,
-    pub dependencies: Dependencies,
+    /// crate folder
+    pub folder: PathBuf,
+    /// deserialized manifest
+    pub manifest: Crate,
 }
 
 impl CrateContext {
@@ -54,11 +176,20 @@ impl CrateContext {
         let manifest = Crate::load(location).await?;
 
         Ok(CrateContext {
-            file: manifest.file,
-            package: manifest.package,
-            dependencies: manifest.dependencies,
+            file: manifest.file.clone(),
+            folder: manifest.folder.clone(),
+            manifest,
         })
     }
+
+    /// The crate, `None` if publishing is disabled using
+    /// `publish = false` or `publish = []`.
+    pub fn published(&self) -> Option<&Crate> {
+        self.manifest
+            .package
+            .is_published()
+            .then_some(&self.manifest)
+    }
 }
 
 #[derive(Debug)]
@@ -72,6 +203,7 @@ pub struct WorkspaceContext {
     /// List of internal workspace crates
     pub crates: Vec<Crate>,
     /// list of projects
+    #[allow(dead_code)]
     pub projects: Vec<String>,
     /// external dependencies
     pub external: Dependencies,
@@ -109,17 +241,22 @@ impl WorkspaceContext {
             })
             .collect::<Vec<_>>();
 
-        println!();
+        eprintln!();
         let before = crates.len();
+        for crt in crates.iter_mut() {
+            if let Some(Publish::Workspace { workspace: true }) = crt.package.publish {
+                crt.package.publish = manifest.workspace.package.publish.clone();
+            }
+        }
         crates.retain(|c| {
-            let retain = c.package.publish.unwrap_or(true);
+            let retain = c.package.is_published();
             if !retain {
-                println!("...skipping {}", c.package.name)
+                eprintln!("...skipping {}", c.package.name)
             }
             retain
         });
         if before != crates.len() {
-            println!();
+            eprintln!();
         }
 
         let projects = crates
@@ -138,61 +275,314 @@ impl WorkspaceContext {
         });
 
         crates.iter_mut().for_each(|crt| {
+            let external = crt
+                .dependencies
+                .iter()
+                .chain(crt.build_dependencies.iter())
+                .chain(crt.dev_dependencies.iter())
+                .filter(|(name, _)| !projects.contains(name))
+                .map(|(name, dependency)| (name.clone(), dependency.clone()))
+                .collect();
+            crt.external = external;
             crt.dependencies.retain(|name, _| projects.contains(name));
             crt.dev_dependencies
+                .retain(|name, _| projects.contains(name));
+            crt.build_dependencies
                 .retain(|name, _| projects.contains(name))
         });
 
-        let mut publish_list = vec![];
-        let mut publish_name_list = vec![];
-        let length = crates.len();
-
-        //should we use while ? maybe while can create infinite loop
-        for _ in 0..length {
-            if publish_list.len() == length {
-                break;
-            }
-            for crt in crates.iter() {
-                if publish_name_list.contains(&crt.name().to_string()) {
-                    continue;
-                }
-                let mut deps = crt
-                    .dependencies
-                    .keys()
-                    .map(|c| c.to_string())
-                    .collect::<Vec<String>>();
-                let mut dev_deps = crt
-                    .dev_dependencies
-                    .keys()
-                    .map(|c| c.to_string())
-                    .collect::<Vec<String>>();
-                deps.retain(|dep| !publish_name_list.contains(dep));
-                dev_deps.retain(|dep| !publish_name_list.contains(dep));
-
-                if deps.is_empty() && dev_deps.is_empty() {
-                    publish_list.push(crt.clone());
-                    publish_name_list.push(crt.name().to_string());
-                }
-            }
-        }
-
-        // println!("=============");
-        // for crt in &publish_list {
-        //     let deps = crt
-        //         .dependencies
-        //         .keys()
-        //         .map(|c| c.to_string())
-        //         .collect::<Vec<String>>();
-        //     println!("{} -> {}", crt.name(), deps.join(", "));
-        // }
+        let crates = publish_order(crates, &manifest.workspace.dependencies)?;
+        let projects = crates
+            .iter()
+            .map(|crt| crt.name().to_string())
+            .collect::<Vec<_>>();
 
         Ok(WorkspaceContext {
             file: manifest.file.clone(),
             folder: folder.to_path_buf(),
             manifest,
-            crates: publish_list,
-            projects: publish_name_list,
+            crates,
+            projects,
             external,
         })
     }
+
+    /// Manifest locations of all workspace members, including
+    /// the ones that are not published.
+    pub fn member_manifests(&self) -> Vec<PathBuf> {
+        self.manifest
+            .workspace
+            .members
+            .iter()
+            .map(|m| self.folder.join(m).join("Cargo.toml"))
+            .collect()
+    }
+
+    /// Publishing layers of member crates, see [`Context::layers`].
+    pub fn layers(&self) -> Vec<Vec<&Crate>> {
+        layers(&self.crates, &self.manifest.workspace.dependencies)
+    }
+
+    /// Version of the given member crate: its own `package.version` if the
+    /// crate is versioned independently, the workspace version otherwise.
+    pub fn crate_version(&self, crt: &Crate) -> Result<Version> {
+        match crt.package.explicit_version()? {
+            Some(version) => Ok(version),
+            None => self.manifest.version(),
+        }
+    }
+}
+
+/// Orders crates so that each crate follows its dependencies. Dev-dependencies
+/// without a version are removed by cargo when publishing and are ignored, while
+/// cycles formed by versioned dev-dependencies are broken with a warning.
+/// Cycles between regular or build dependencies result in an error.
+fn publish_order(crates: Vec<Crate>, workspace: &Dependencies) -> Result<Vec<Crate>> {
+    let names = crates
+        .iter()
+        .map(|crt| crt.name().to_string())
+        .collect::<Vec<_>>();
+
+    let mut remaining = crates
+        .into_iter()
+        .map(|crt| {
+            let internal = |dep: &String| dep.as_str() != crt.name() && names.contains(dep);
+            // build-dependencies are required to publish the crate just like regular ones
+            let mut dependencies = crt
+                .dependencies
+                .keys()
+                .chain(crt.build_dependencies.keys())
+                .filter(|dep| internal(dep))
+                .cloned()
+                .collect::<Vec<_>>();
+            dependencies.sort();
+            dependencies.dedup();
+            let dev_dependencies = crt
+                .dev_dependencies
+                .iter()
+                .filter(|(dep, info)| {
+                    internal(dep)
+                        && !dependencies.contains(dep)
+                        && is_published_dev_dependency(dep, info, workspace)
+                })
+                .map(|(dep, _)| dep.clone())
+                .collect::<Vec<_>>();
+            (crt, dependencies, dev_dependencies)
+        })
+        .collect::<Vec<_>>();
+
+    let mut order: Vec<Crate> = vec![];
+    let mut placed = HashSet::<String>::new();
+
+    while !remaining.is_empty() {
+        let before = remaining.len();
+        let mut idx = 0;
+        while idx < remaining.len() {
+            let (_, dependencies, dev_dependencies) = &remaining[idx];
+            if dependencies
+                .iter()
+                .chain(dev_dependencies.iter())
+                .all(|dep| placed.contains(dep))
+            {
+                let (crt, _, _) = remaining.remove(idx);
+                placed.insert(crt.name().to_string());
+                order.push(crt);
+            } else {
+                idx += 1;
+            }
+        }
+        if remaining.len() < before {
+            continue;
+        }
+
+        // no crate can be placed: break a dev-dependency cycle if possible
+        let ready = remaining
+            .iter()
+            .position(|(_, dependencies, _)| dependencies.iter().all(|dep| placed.contains(dep)));
+        let Some(idx) = ready else {
+            return Err(error!(
+                "dependency cycle detected: {}",
+                find_cycle(&remaining, &placed).join(" -> ")
+            ));
+        };
+
+        let (crt, _, dev_dependencies) = remaining.remove(idx);
+        let pending = dev_dependencies
+            .iter()
+            .filter(|dep| !placed.contains(*dep))
+            .map(|dep| format!("`{dep}`"))
+            .collect::<Vec<_>>();
+        // printed to stderr to keep the output of commands such as `check --format json` clean
+        eprintln!(
+            "{:>12} `{}` is published before its dev-dependencies {} to break a dependency cycle",
+            style("Cycle").yellow().bold(),
+            crt.name(),
+            pending.join(", ")
+        );
+        placed.insert(crt.name().to_string());
+        order.push(crt);
+    }
+
+    Ok(order)
+}
+
+/// Groups crates given in the publishing order into layers.
+fn layers<'a>(crates: &'a [Crate], workspace: &Dependencies) -> Vec<Vec<&'a Crate>> {
+    let mut depth = HashMap::<&str, usize>::new();
+    let mut layers: Vec<Vec<&Crate>> = vec![];
+    // crates are in the publishing order, dependencies precede their dependents
+    for crt in crates.iter() {
+        let layer = crt
+            .dependencies
+            .keys()
+            .chain(crt.build_dependencies.keys())
+            .chain(
+                crt.dev_dependencies
+                    .iter()
+                    .filter(|(dep, info)| is_published_dev_dependency(dep, info, workspace))
+                    .map(|(dep, _)| dep),
+            )
+            .filter_map(|dep| depth.get(dep.as_str()))
+            .map(|layer| layer + 1)
+            .max()
+            .unwrap_or(0);
+        depth.insert(crt.name(), layer);
+        if layers.len() == layer {
+            layers.push(vec![]);
+        }
+        layers[layer].push(crt);
+    }
+    layers
+}
+
+/// Returns `true` if the dev-dependency specifies a version, directly or in
+/// `workspace.dependencies`; cargo removes the others when publishing.
+fn is_published_dev_dependency(
+    name: &str,
+    dependency: &Dependency,
+    workspace: &Dependencies,
+) -> bool {
+    dependency.has_version()
+        || dependency.is_workspace() && workspace.get(name).is_some_and(Dependency::has_version)
+}
+
+/// Follows unplaced regular and build dependencies of the remaining crates (each of which
+/// has at least one) until a crate repeats, returning the cycle.
+fn find_cycle(
+    remaining: &[(Crate, Vec<String>, Vec<String>)],
+    placed: &HashSet<String>,
+) -> Vec<String> {
+    let mut path = vec![remaining[0].0.name().to_string()];
+    loop {
+        let current = path.last().unwrap();
+        let next = remaining
+            .iter()
+            .find(|(crt, _, _)| crt.name() == current)
+            .and_then(|(_, dependencies, _)| dependencies.iter().find(|dep| !placed.contains(*dep)))
+            .cloned();
+        let Some(next) = next else {
+            return path;
+        };
+        if let Some(start) = path.iter().position(|name| *name == next) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(next);
+            return cycle;
+        }
+        path.push(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Crate depending on the given crates, dev-dependencies
+    /// being versioned if `true`.
+    fn crt(name: &str, dependencies: &[&str], build: &[&str], dev: &[(&str, bool)]) -> Crate {
+        let dependency = |versioned: bool| -> Dependency {
+            let value = if versioned {
+                Value::String("0.1.0".to_string())
+            } else {
+                toml::from_str::<Value>(r#"path = "..""#).unwrap()
+            };
+            value.try_into().unwrap()
+        };
+        let mut crt = toml::from_str::<Crate>(&format!(
+            "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n"
+        ))
+        .unwrap();
+        for dep in dependencies {
+            crt.dependencies.insert(dep.to_string(), dependency(true));
+        }
+        for dep in build {
+            crt.build_dependencies
+                .insert(dep.to_string(), dependency(true));
+        }
+        for (dep, versioned) in dev {
+            crt.dev_dependencies
+                .insert(dep.to_string(), dependency(*versioned));
+        }
+        crt
+    }
+
+    fn names(crates: &[Crate]) -> Vec<&str> {
+        crates.iter().map(Crate::name).collect()
+    }
+
+    #[test]
+    fn publish_order_follows_dependencies() {
+        let crates = vec![
+            crt("app", &["core", "macros"], &[], &[]),
+            crt("macros", &[], &["core"], &[]),
+            crt("core", &[], &[], &[("app", false)]),
+        ];
+        let order = publish_order(crates, &Dependencies::new()).unwrap();
+        assert_eq!(names(&order), ["core", "macros", "app"]);
+    }
+
+    #[test]
+    fn publish_order_breaks_dev_dependency_cycles() {
+        let crates = vec![
+            crt("testing", &["core"], &[], &[]),
+            crt("core", &[], &[], &[("testing", true)]),
+            crt("app", &["core"], &[], &[("testing", true)]),
+        ];
+        let order = publish_order(crates, &Dependencies::new()).unwrap();
+        assert_eq!(names(&order), ["core", "testing", "app"]);
+    }
+
+    #[test]
+    fn layers_ignore_unversioned_dev_dependencies() {
+        let crates = vec![
+            crt("core", &[], &[], &[]),
+            crt("testing", &["core"], &[], &[]),
+            crt("macros", &["core"], &[], &[("testing", false)]),
+            crt("app", &["core"], &[], &[("testing", true)]),
+        ];
+        let workspace = Dependencies::new();
+        let order = publish_order(crates, &workspace).unwrap();
+        let layers = layers(&order, &workspace)
+            .into_iter()
+            .map(|layer| layer.into_iter().map(Crate::name).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layers,
+            [vec!["core"], vec!["testing", "macros"], vec!["app"]]
+        );
+    }
+
+    #[test]
+    fn publish_order_reports_cycles() {
+        let crates = vec![
+            crt("app", &["a"], &[], &[]),
+            crt("a", &["b"], &[], &[]),
+            crt("b", &[], &["c"], &[]),
+            crt("c", &["a"], &[], &[]),
+        ];
+        let err = publish_order(crates, &Dependencies::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("a -> b -> c -> a"), "{err}");
+        assert!(!err.contains("app"), "{err}");
+    }
 }
@@ -1,51 +1,170 @@
 use crate::prelude::*;
+use futures_util::stream::{self, StreamExt};
+use std::time::Duration;
 
+/// maximum number of registry queries in flight
+const CONCURRENCY: usize = 8;
+/// maximum number of retries of throttled (429) or failed (5xx) requests
+const MAX_RETRIES: u32 = 5;
+
+/// Registry client querying crates.io or an alternate registry through its
+/// sparse index. Unlike the crates.io API, the index is not subject to the
+/// crawler rate limit, so lookups are issued concurrently.
 pub struct CratesIo {
-    client: crates_io_api::AsyncClient,
-    rate_limit: std::time::Duration,
+    index: RegistryIndex,
 }
 
 impl CratesIo {
-    pub fn new() -> Self {
-        Self::new_with_rate_limit(0)
-    }
-    pub fn new_with_rate_limit(rate_limit: u64) -> Self {
-        let client = crates_io_api::AsyncClient::new(
-            "cargo-emanate (info@aspectron.com)",
-            std::time::Duration::from_millis(0),
-        )
-        .unwrap_or_else(|err| panic!("Unable to instantiate crates_io_api::AsyncClient: `{err}`"));
-
-        CratesIo {
-            client,
-            rate_limit: std::time::Duration::from_millis(rate_limit),
-        }
+    /// Client of the given registry.
+    pub fn with_registry(registry: &Registry) -> Result<Self> {
+        Ok(CratesIo {
+            index: RegistryIndex::for_registry(registry)?,
+        })
+    }
+
+    /// Crate published under the given name along with all its versions,
+    /// `None` if the crate is not present in the registry.
+    pub async fn get_crate(&self, name: &str) -> Result<Option<RegistryCrate>> {
+        self.index.get_crate(name).await
+    }
+
+    /// Queries the given crates concurrently, returning
+    /// the results in the order of the names.
+    pub async fn get_crates(&self, names: &[&str]) -> Vec<Result<Option<RegistryCrate>>> {
+        stream::iter(names)
+            .map(|name| self.get_crate(name))
+            .buffered(CONCURRENCY)
+            .collect()
+            .await
     }
 
     pub async fn get_latest_version(&self, name: &str) -> Result<Version> {
-        let crt = self.client.get_crate(name).await?;
-        let mut versions = crt
-            .versions
+        self.get_crate(name)
+            .await?
+            .ok_or_else(|| error!("crate `{name}` not found"))?
+            .latest()
+            .ok_or_else(|| error!("no released versions present for crate `{name}`"))
+    }
+}
+
+/// Crate present in a registry.
+#[derive(Debug, Clone)]
+pub struct RegistryCrate {
+    /// name of the crate in the registry, may differ from the requested
+    /// name by case or by `-` and `_`
+    pub name: String,
+    pub versions: Vec<CrateVersion>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrateVersion {
+    pub version: Version,
+    pub yanked: bool,
+}
+
+impl RegistryCrate {
+    /// Latest release version, ignoring yanked and pre-release versions.
+    pub fn latest(&self) -> Option<Version> {
+        self.versions
             .iter()
-            .map(|v| {
-                v.num.parse::<Version>().unwrap_or_else(|err| {
-                    panic!(
-                        "Unable to parse version for crate `{name}` - `{}`: {err}",
-                        v.num
-                    );
-                })
-            })
-            .collect::<Vec<_>>();
-        // remove all versions containing suffixes
-        versions.retain(|v| v.suffix.is_none());
-        versions.sort_by(|a, b| b.cmp(a));
-        let version = versions
-            .first()
-            .unwrap_or_else(|| panic!("No versions present for crate {name}"))
-            .to_owned();
-
-        std::thread::sleep(self.rate_limit);
-
-        Ok(version)
+            .filter(|v| !v.yanked && v.version.suffix.is_none())
+            .map(|v| v.version.clone())
+            .max()
+    }
+
+    /// Latest release version semver compatible with the given version.
+    pub fn latest_compatible(&self, version: &Version) -> Option<Version> {
+        self.versions
+            .iter()
+            .filter(|v| !v.yanked && v.version.suffix.is_none())
+            .filter(|v| version.is_compatible(&v.version))
+            .map(|v| v.version.clone())
+            .max()
+    }
+
+    /// Returns `true` if the version has been published (including yanked versions).
+    pub fn contains(&self, version: &Version) -> bool {
+        self.versions.iter().any(|v| &v.version == version)
+    }
+}
+
+/// Sends the request, retrying throttled (429) and failed (5xx) requests
+/// with an exponential backoff or after the delay requested by the
+/// `Retry-After` header.
+pub async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+    let mut backoff = Duration::from_secs(1);
+    let mut retries = 0;
+    loop {
+        let attempt = request
+            .try_clone()
+            .ok_or_else(|| error!("unable to retry the request"))?;
+        let response = attempt.send().await?;
+        let status = response.status();
+        if retries >= MAX_RETRIES
+            || !(status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+        {
+            return Ok(response);
+        }
+
+        let delay = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(backoff);
+        tokio::time::sleep(delay).await;
+        backoff *= 2;
+        retries += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::serve;
+    use std::time::Instant;
+
+    async fn get(url: &str) -> (u16, String) {
+        let response = send(reqwest::Client::new().get(url)).await.unwrap();
+        (response.status().as_u16(), response.text().await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn send_retries_after_requested_delay() {
+        let url = serve(vec![
+            Some((429, "Retry-After: 0\r\n", "throttled")),
+            Some((503, "Retry-After: 0\r\n", "unavailable")),
+            Some((200, "", "ok")),
+        ]);
+        assert_eq!(get(&url).await, (200, "ok".to_string()));
+    }
+
+    #[tokio::test]
+    async fn send_backs_off_without_retry_after() {
+        let url = serve(vec![Some((500, "", "failed")), Some((200, "", "ok"))]);
+        let start = Instant::now();
+        assert_eq!(get(&url).await, (200, "ok".to_string()));
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn send_gives_up_after_max_retries() {
+        let url = serve(vec![
+            Some((500, "Retry-After: 0\r\n", "0")),
+            Some((500, "Retry-After: 0\r\n", "1")),
+            Some((500, "Retry-After: 0\r\n", "2")),
+            Some((500, "Retry-After: 0\r\n", "3")),
+            Some((500, "Retry-After: 0\r\n", "4")),
+            Some((500, "Retry-After: 0\r\n", "5")),
+            Some((200, "", "ok")),
+        ]);
+        assert_eq!(get(&url).await, (500, MAX_RETRIES.to_string()));
+    }
+
+    #[tokio::test]
+    async fn send_does_not_retry_client_errors() {
+        let url = serve(vec![Some((404, "", "missing")), Some((200, "", "ok"))]);
+        assert_eq!(get(&url).await, (404, "missing".to_string()));
     }
 }
@@ -28,9 +28,6 @@ pub enum Error {
     #[error("invalid version {0}")]
     InvalidVersion(String),
 
-    #[error(transparent)]
-    CratesIoApi(#[from] crates_io_api::Error),
-
     #[error("versions containing \"*\" are not allowed")]
     VersionAsterisk,
 
@@ -43,6 +40,9 @@ pub enum Error {
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
 
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
     #[error("ZIP error: {0}")]
     Zip(#[from] zip::result::ZipError),
 
@@ -0,0 +1,107 @@
+use crate::prelude::*;
+
+/// Local git repository accessed via the `git` command line.
+pub struct Git {
+    /// repository root folder
+    pub root: PathBuf,
+}
+
+impl Git {
+    /// Opens the git repository containing the given folder.
+    pub fn open(folder: &Path) -> Result<Git> {
+        let root = read_git(folder, &["rev-parse", "--show-toplevel"])
+            .map_err(|err| error!("`{}` is not in a git repository: {err}", folder.display()))?;
+        Ok(Git {
+            root: PathBuf::from(root).canonicalize()?,
+        })
+    }
+
+    /// Executes git with the given arguments, returning its trimmed output.
+    pub fn read(&self, args: &[&str]) -> Result<String> {
+        read_git(&self.root, args)
+    }
+
+    /// Most recent tag matching the given glob pattern (i.e. `v*`),
+    /// reachable from `HEAD`.
+    pub fn last_tag(&self, pattern: &str) -> Result<Option<String>> {
+        match self.read(&["describe", "--tags", "--abbrev=0", "--match", pattern]) {
+            Ok(tag) => Ok(Some(tag)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Returns `true` if there are no uncommitted changes to tracked files.
+    pub fn is_clean(&self) -> Result<bool> {
+        Ok(self
+            .read(&["status", "--porcelain", "--untracked-files=no"])?
+            .is_empty())
+    }
+
+    /// Stages the given files and commits them with the given message.
+    pub fn commit(&self, files: &[PathBuf], message: &str) -> Result<()> {
+        let mut args = vec!["add".to_string(), "--".to_string()];
+        args.extend(files.iter().map(|file| file.to_string_lossy().to_string()));
+        self.read(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+        self.read(&["commit", "-m", message])?;
+        Ok(())
+    }
+
+    /// Creates an annotated tag pointing to `HEAD`.
+    pub fn tag(&self, tag: &str, message: &str) -> Result<()> {
+        self.read(&["tag", "-a", tag, "-m", message])?;
+        Ok(())
+    }
+
+    /// Absolute paths of files changed between `since` and `HEAD`.
+    pub fn changed_files(&self, since: &str) -> Result<Vec<PathBuf>> {
+        let files = self.read(&["diff", "--name-only", since, "HEAD", "--"])?;
+        Ok(files
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|file| self.root.join(file))
+            .collect())
+    }
+
+    /// Commits made since the given reference (all commits if `None`),
+    /// optionally limited to the ones touching the given folder.
+    pub fn commits(&self, since: Option<&str>, folder: Option<&Path>) -> Result<Vec<Commit>> {
+        let range = since
+            .map(|since| format!("{since}..HEAD"))
+            .unwrap_or("HEAD".to_string());
+        let folder = folder.map(|folder| folder.to_string_lossy().to_string());
+        let mut args = vec!["log", "--format=%H%x1f%s%x1f%b%x1e", &range, "--"];
+        if let Some(folder) = folder.as_ref() {
+            args.push(folder);
+        }
+
+        let log = self.read(&args)?;
+        Ok(log
+            .split('\x1e')
+            .filter_map(|entry| {
+                let mut fields = entry.trim_start_matches('\n').split('\x1f');
+                let hash = fields.next().filter(|hash| !hash.is_empty())?;
+                let subject = fields.next().unwrap_or_default();
+                let body = fields.next().unwrap_or_default();
+                Some(Commit::parse(hash, subject, body.trim()))
+            })
+            .collect())
+    }
+}
+
+fn read_git(folder: &Path, args: &[&str]) -> Result<String> {
+    let output = cmd("git", args)
+        .dir(folder)
+        .stdout_capture()
+        .stderr_capture()
+        .unchecked()
+        .run()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(error!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
@@ -0,0 +1,242 @@
+use crate::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Client of a sparse registry index (<https://doc.rust-lang.org/cargo/reference/registry-index.html>),
+/// used to determine when a published version becomes available to cargo.
+pub struct RegistryIndex {
+    url: String,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    name: String,
+    vers: String,
+    #[serde(default)]
+    yanked: bool,
+}
+
+impl RegistryIndex {
+    pub fn new(url: &str) -> Self {
+        let url = url.trim_start_matches("sparse+");
+        let url = if url.ends_with('/') {
+            url.to_string()
+        } else {
+            format!("{url}/")
+        };
+
+        RegistryIndex {
+            url,
+            token: None,
+            client: reqwest::Client::builder()
+                .user_agent("cargo-emanate (info@aspectron.com)")
+                .build()
+                .unwrap_or_else(|err| panic!("Unable to instantiate reqwest::Client: `{err}`")),
+        }
+    }
+
+    /// Index of the given sparse registry.
+    pub fn for_registry(registry: &Registry) -> Result<Self> {
+        let mut index = Self::new(registry.sparse_index()?);
+        index.token = registry.token.clone();
+        Ok(index)
+    }
+
+    /// Index file URL of the crate.
+    fn crate_url(&self, name: &str) -> String {
+        let name = name.to_lowercase();
+        let path = match name.len() {
+            1 => format!("1/{name}"),
+            2 => format!("2/{name}"),
+            3 => format!("3/{}/{name}", &name[..1]),
+            _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+        };
+        format!("{}{path}", self.url)
+    }
+
+    /// Index entries of the crate, `None` if the crate is not found.
+    async fn entries(&self, name: &str) -> Result<Option<Vec<IndexEntry>>> {
+        let mut request = self
+            .client
+            .get(self.crate_url(name))
+            .header("Cache-Control", "no-cache");
+        if let Some(token) = self.token.as_ref() {
+            request = request.header("Authorization", token);
+        }
+        let response = send(request).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let text = response.error_for_status()?.text().await?;
+
+        Ok(Some(
+            text.lines()
+                .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+                .collect(),
+        ))
+    }
+
+    /// Versions of the crate present in the index, empty if the crate is not found.
+    pub async fn versions(&self, name: &str) -> Result<Vec<Version>> {
+        Ok(self
+            .entries(name)
+            .await?
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|entry| entry.vers.parse::<Version>().ok())
+            .collect())
+    }
+
+    /// Crate published under the given name, `None` if the name is not taken.
+    /// As registries treat `-` and `_` as equivalent, the crate found may
+    /// carry a different name.
+    pub async fn get_crate(&self, name: &str) -> Result<Option<RegistryCrate>> {
+        let alternate = if name.contains('-') {
+            name.replace('-', "_")
+        } else {
+            name.replace('_', "-")
+        };
+        let mut entries = self.entries(name).await?;
+        if entries.is_none() && alternate != name {
+            entries = self.entries(&alternate).await?;
+        }
+        let Some(entries) = entries else {
+            return Ok(None);
+        };
+
+        Ok(Some(RegistryCrate {
+            name: entries
+                .first()
+                .map(|entry| entry.name.clone())
+                .unwrap_or(name.to_string()),
+            versions: entries
+                .into_iter()
+                .filter_map(|entry| {
+                    Some(CrateVersion {
+                        version: entry.vers.parse().ok()?,
+                        yanked: entry.yanked,
+                    })
+                })
+                .collect(),
+        }))
+    }
+
+    /// Returns `true` if the given version of the crate is present in the index.
+    pub async fn has_version(&self, name: &str, version: &Version) -> Result<bool> {
+        Ok(self.versions(name).await?.contains(version))
+    }
+
+    /// Polls the index until the given version of the crate becomes visible,
+    /// returning `false` if it does not appear within the timeout.
+    pub async fn wait(
+        &self,
+        name: &str,
+        version: &Version,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<bool> {
+        let start = Instant::now();
+        let visible = loop {
+            match self.has_version(name, version).await {
+                Ok(true) => break true,
+                Ok(false) => {}
+                Err(err) => {
+                    // transient index errors must not abort the publish
+                    log_state_clear();
+                    log_warn!("Index", "{name} {version}: {err}");
+                }
+            }
+            if start.elapsed() >= timeout {
+                break false;
+            }
+            log_state!(
+                "Waiting",
+                "{name} {version} to appear in the index ({}s)",
+                start.elapsed().as_secs()
+            );
+            tokio::time::sleep(poll_interval).await;
+        };
+        log_state_clear();
+
+        Ok(visible)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Response of the stand-in registry server: the status,
+    /// additional header lines and the body.
+    pub(crate) type Response = Option<(u16, &'static str, &'static str)>;
+
+    /// Serves the given responses to consecutive requests, `None` dropping
+    /// the connection without a response. The last response is repeated.
+    pub(crate) fn serve(responses: Vec<Response>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for (n, stream) in listener.incoming().enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buffer = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(len) => request.extend_from_slice(&buffer[..len]),
+                    }
+                }
+                if let Some((status, headers, body)) = responses[n.min(responses.len() - 1)] {
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 {status} X\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                }
+            }
+        });
+        url
+    }
+
+    const ENTRY: &str = r#"{"name":"emanate-test","vers":"1.0.0","yanked":false}"#;
+
+    #[tokio::test]
+    async fn wait_survives_transient_errors() {
+        let url = serve(vec![
+            None,
+            Some((404, "", "")),
+            None,
+            Some((200, "", ENTRY)),
+        ]);
+        let index = RegistryIndex::new(&format!("sparse+{url}"));
+        let visible = index
+            .wait(
+                "emanate-test",
+                &"1.0.0".parse().unwrap(),
+                Duration::from_secs(10),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+        assert!(visible);
+    }
+
+    #[tokio::test]
+    async fn wait_times_out() {
+        let url = serve(vec![None]);
+        let index = RegistryIndex::new(&url);
+        let visible = index
+            .wait(
+                "emanate-test",
+                &"1.0.0".parse().unwrap(),
+                Duration::from_millis(100),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap();
+        assert!(!visible);
+    }
+}
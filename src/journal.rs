@@ -0,0 +1,176 @@
+use crate::prelude::*;
+use serde::Serialize;
+use std::sync::{Mutex, MutexGuard};
+
+/// Publishing state of a crate, in the order of progression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PublishState {
+    Pending,
+    Packaged,
+    Uploaded,
+    /// the version is visible in the registry index
+    Visible,
+}
+
+impl std::fmt::Display for PublishState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PublishState::Pending => write!(f, "pending"),
+            PublishState::Packaged => write!(f, "packaged"),
+            PublishState::Uploaded => write!(f, "uploaded"),
+            PublishState::Visible => write!(f, "visible"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub name: String,
+    pub version: String,
+    pub state: PublishState,
+    /// last error encountered while publishing the crate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalData {
+    version: String,
+    crates: Vec<JournalEntry>,
+}
+
+/// Publish progress persisted in `target/emanate/publish-<version>.json`,
+/// allowing an interrupted `publish` to be resumed. The journal can be
+/// shared between concurrent uploads.
+#[derive(Debug)]
+pub struct PublishJournal {
+    pub file: PathBuf,
+    data: Mutex<JournalData>,
+}
+
+impl PublishJournal {
+    /// Journal file for the given release version.
+    pub fn location(ctx: &Context, version: &Version) -> PathBuf {
+        let target = std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| ctx.folder().join("target"));
+        target
+            .join("emanate")
+            .join(format!("publish-{version}.json"))
+    }
+
+    /// Creates a journal with all crates in the pending state.
+    pub fn new(ctx: &Context, version: &Version) -> Result<PublishJournal> {
+        Ok(PublishJournal {
+            file: Self::location(ctx, version),
+            data: Mutex::new(JournalData {
+                version: version.to_string(),
+                crates: Self::entries(ctx)?,
+            }),
+        })
+    }
+
+    fn entries(ctx: &Context) -> Result<Vec<JournalEntry>> {
+        let mut crates = vec![];
+        for crt in ctx.crates() {
+            crates.push(JournalEntry {
+                name: crt.name().to_string(),
+                version: ctx.crate_version(crt)?.to_string(),
+                state: PublishState::Pending,
+                error: None,
+            });
+        }
+        Ok(crates)
+    }
+
+    /// Loads the journal of a previous `publish` run, adding crates
+    /// missing from the journal in the pending state.
+    pub fn load(ctx: &Context, version: &Version) -> Result<PublishJournal> {
+        let file = Self::location(ctx, version);
+        if !file.exists() {
+            return Err(error!(
+                "unable to resume: publish journal `{}` not found",
+                file.display()
+            ));
+        }
+
+        let mut data: JournalData = serde_json::from_str(&fs::read_to_string(&file)?)?;
+        for entry in Self::entries(ctx)? {
+            match data.crates.iter_mut().find(|e| e.name == entry.name) {
+                // a changed crate version invalidates its progress
+                Some(existing) if existing.version != entry.version => *existing = entry,
+                Some(_) => {}
+                None => data.crates.push(entry),
+            }
+        }
+
+        Ok(PublishJournal {
+            file,
+            data: Mutex::new(data),
+        })
+    }
+
+    fn data(&self) -> MutexGuard<'_, JournalData> {
+        self.data.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn store(&self) -> Result<()> {
+        self.store_data(&self.data())
+    }
+
+    fn store_data(&self, data: &JournalData) -> Result<()> {
+        if let Some(folder) = self.file.parent() {
+            fs::create_dir_all(folder)?;
+        }
+        fs::write(&self.file, serde_json::to_string_pretty(data)?)?;
+        Ok(())
+    }
+
+    pub fn state(&self, name: &str) -> PublishState {
+        self.data()
+            .crates
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.state)
+            .unwrap_or(PublishState::Pending)
+    }
+
+    /// Updates the state of the crate and stores the journal.
+    pub fn set_state(&self, name: &str, state: PublishState) -> Result<()> {
+        let mut data = self.data();
+        if let Some(entry) = data.crates.iter_mut().find(|entry| entry.name == name) {
+            entry.state = state;
+            entry.error = None;
+        }
+        self.store_data(&data)
+    }
+
+    /// Records the error for the crate and stores the journal.
+    pub fn set_error(&self, name: &str, error: String) -> Result<()> {
+        let mut data = self.data();
+        if let Some(entry) = data.crates.iter_mut().find(|entry| entry.name == name) {
+            entry.error = Some(error);
+        }
+        self.store_data(&data)
+    }
+
+    /// Prints the state of each crate.
+    pub fn report(&self) {
+        let data = self.data();
+        let len = data
+            .crates
+            .iter()
+            .map(|entry| entry.name.len() + entry.version.len() + 1)
+            .fold(0, |a, b| a.max(b));
+        for entry in data.crates.iter() {
+            let name = format!("{} {}", entry.name, entry.version);
+            let name = name.pad(len, ' ', Alignment::Left, false);
+            match &entry.error {
+                Some(error) => log_error!("Report", "{name} {} - {error}", entry.state),
+                None => log_info!("Report", "{name} {}", entry.state),
+            }
+        }
+        log_info!("Journal", "`{}`", self.file.display());
+    }
+}
@@ -94,6 +94,7 @@ macro_rules! log_state {
 pub use log_error;
 pub use log_info;
 pub use log_state;
+#[allow(unused_imports)]
 pub use log_trace;
 pub use log_warn;
 
@@ -3,19 +3,28 @@ use crate::result::Result;
 use clap::{Parser, Subcommand};
 
 mod archive;
+mod audit;
 mod build;
+mod changelog;
 mod check;
+mod commit;
 mod context;
 mod crates;
 mod error;
+mod git;
+mod index;
+mod journal;
 mod log;
 mod manifest;
 mod owner;
 mod prelude;
 mod publish;
+mod registry;
 mod result;
 mod utils;
+mod validate;
 mod version;
+mod yank;
 
 #[derive(Debug, Parser)]
 #[clap(name = "cargo")]
@@ -47,29 +56,78 @@ struct Args {
     /// Perform checks without actual processing.
     #[clap(short, long)]
     dry_run: bool,
+
+    /// Registry to use instead of crates.io, configured in `.cargo/config.toml`
+    #[clap(long, global = true)]
+    registry: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Action {
     // Test {},
-    /// Update workspace version: 'major', 'minor', 'patch', 'x.y.z[-suffix]'
-    Version { change: Change },
+    /// Update workspace version: 'major', 'minor', 'patch', 'pre [alpha|beta|rc]', 'premajor|preminor|prepatch <alpha|beta|rc>', 'release', 'auto', 'x.y.z[-suffix]'
+    Version {
+        #[clap(num_args = 1..=2, required = true)]
+        change: Vec<String>,
+        /// Bump only the given independently versioned crates
+        #[clap(short, long)]
+        package: Option<Vec<String>>,
+        /// Bump only crates changed since the given git reference (defaults to the last release tag) and their dependents
+        #[clap(long, value_name = "GIT-REF", conflicts_with = "package")]
+        changed_since: Option<Option<String>>,
+        /// Commit the modified files
+        #[clap(long)]
+        commit: bool,
+        /// Commit the modified files and create release tags
+        #[clap(long)]
+        tag: bool,
+    },
     /// Publish all crates in the workspace
-    Publish,
-    /// Check all dependency versions against those published on crates.io
-    Check,
+    Publish {
+        /// Continue an interrupted publish from the first unfinished crate
+        #[clap(long)]
+        resume: bool,
+    },
+    /// Check all dependency versions against those published on crates.io (or their registries)
+    Check {
+        /// Update the given dependencies (all if none are given) to their latest versions
+        #[clap(long, num_args = 0.., value_name = "NAME")]
+        update: Option<Vec<String>>,
+        /// Update only to the latest semver compatible versions
+        #[clap(long, requires = "update")]
+        compatible_only: bool,
+        /// Output format
+        #[clap(long, value_enum, default_value_t)]
+        format: check::Format,
+        /// Exit with a non-zero status if any dependency matches the condition
+        #[clap(long, value_enum)]
+        fail_on: Option<check::FailOn>,
+    },
+    /// Check workspace crates against the version policy (exits with a non-zero status on violations)
+    Audit,
     /// Build WASM package targets specified within cargo's `package.metadata.emanate.wasm`
     Build {
         #[clap(short, long)]
         package: Option<Vec<String>>,
     },
-    /// Add or remove a crate owner on crates.io
+    /// Add or remove a crate owner on crates.io (or the registry the crates are published to)
     Owner {
         #[clap(long)]
         add: Option<String>,
         #[clap(long)]
         remove: Option<String>,
     },
+    /// Yank a released version of all crates from crates.io (or the registry the crates are published to)
+    Yank {
+        /// Version to yank
+        version: String,
+        /// Undo the yank, allowing the version to be used again
+        #[clap(long)]
+        undo: bool,
+        /// Yank only the given crates
+        #[clap(short, long)]
+        package: Option<Vec<String>>,
+    },
 }
 
 pub async fn async_main() -> Result<()> {
@@ -78,6 +136,7 @@ pub async fn async_main() -> Result<()> {
         action,
         location,
         dry_run,
+        registry,
     }) = args;
     let location = manifest::locate(location).await?;
     let ctx = Context::load(&location).await?;
@@ -90,19 +149,38 @@ pub async fn async_main() -> Result<()> {
         //     let v = client.get_latest_version("base64").await?;
         //     println!("{}", v);
         // }
-        Action::Version { change } => {
-            let versioner = Versioner::new(ctx);
-            versioner.change(change)?;
+        Action::Version {
+            change,
+            package,
+            changed_since,
+            commit,
+            tag,
+        } => {
+            let change = change.join(" ").parse::<Change>()?;
+            let versioner = Versioner::new(ctx, dry_run);
+            versioner.change(change, package, changed_since, commit, tag)?;
         }
 
-        Action::Publish => {
-            let publisher = Publisher::new(ctx);
-            publisher.publish(dry_run).await?;
+        Action::Publish { resume } => {
+            let publisher = Publisher::new(ctx, registry);
+            publisher.publish(dry_run, resume).await?;
         }
 
-        Action::Check => {
-            let checker = Checker::new(ctx);
-            checker.check().await?;
+        Action::Check {
+            update,
+            compatible_only,
+            format,
+            fail_on,
+        } => {
+            let checker = Checker::new(ctx, registry);
+            checker
+                .check(update, compatible_only, format, fail_on, dry_run)
+                .await?;
+        }
+
+        Action::Audit => {
+            let auditor = Auditor::new(ctx);
+            auditor.audit().await?;
         }
 
         Action::Build { package } => {
@@ -119,10 +197,21 @@ pub async fn async_main() -> Result<()> {
                 return Err("Please specify either --add or --remove".into());
             };
 
-            let owner = Owner::new(ctx); //, add, remove);
+            let owner = Owner::new(ctx, registry); //, add, remove);
             owner.change(action, username).await?;
             // owner.run().await?;
         }
+
+        Action::Yank {
+            version,
+            undo,
+            package,
+        } => {
+            let yanker = Yanker::new(ctx, registry);
+            yanker
+                .yank(version.parse()?, undo, package, dry_run)
+                .await?;
+        }
     }
 
     Ok(())
@@ -131,7 +220,8 @@ pub async fn async_main() -> Result<()> {
 #[tokio::main]
 async fn main() -> Result<()> {
     if let Err(err) = async_main().await {
-        println!("{err}")
+        eprintln!("{err}");
+        std::process::exit(1);
     }
     Ok(())
 }
@@ -14,9 +14,16 @@ pub struct Crate {
     pub toml_root: Option<Value>,
     pub package: Package,
     // #[serde(skip)]
+    #[serde(default)]
     pub dependencies: Dependencies,
     #[serde(skip, rename(deserialize = "dev-dependencies"))]
     pub dev_dependencies: Dependencies,
+    #[serde(skip, rename(deserialize = "build-dependencies"))]
+    pub build_dependencies: Dependencies,
+    /// dependencies on crates outside of the workspace declared by the
+    /// crate itself (regular, build and dev, including target-specific ones)
+    #[serde(skip)]
+    pub external: Dependencies,
 }
 
 impl Crate {
@@ -34,6 +41,14 @@ impl Crate {
                 }
             }
         }
+        if let Some(build_dependencies) = table.get("build-dependencies") {
+            if let Some(deps) = build_dependencies.as_table() {
+                for (k3, v3) in deps.iter() {
+                    crt.build_dependencies
+                        .insert(k3.clone(), Dependency(v3.clone()));
+                }
+            }
+        }
 
         let targets = table.get("target");
         if let Some(targets) = targets {
@@ -55,6 +70,13 @@ impl Crate {
                                             .insert(k3.clone(), Dependency(v3.clone()));
                                     }
                                 }
+                            } else if k2 == "build-dependencies" {
+                                if let Some(deps) = v2.as_table() {
+                                    for (k3, v3) in deps.iter() {
+                                        crt.build_dependencies
+                                            .insert(k3.clone(), Dependency(v3.clone()));
+                                    }
+                                }
                             }
                         }
                     }
@@ -96,20 +118,114 @@ impl Crate {
         }
         Ok(None)
     }
+
+    /// Returns `true` if the crate is versioned independently
+    /// of the workspace (`package.metadata.emanate.independent`).
+    pub fn is_independent(&self) -> Result<bool> {
+        Ok(self
+            .metadata()?
+            .and_then(|metadata| metadata.independent)
+            .unwrap_or(false))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Metadata {
     pub wasm: Option<WasmMetadata>,
     pub build: Option<BuildMetadata>,
+    pub independent: Option<bool>,
+    pub changelog: Option<ChangelogMetadata>,
+    pub git: Option<GitMetadata>,
+    pub version: Option<VersionMetadata>,
+    pub publish: Option<PublishMetadata>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
 pub struct Package {
     pub name: String,
     pub version: Value,
-    pub publish: Option<bool>,
+    pub publish: Option<Publish>,
     pub metadata: Option<Value>,
+    pub description: Option<Inheritable<String>>,
+    pub license: Option<Inheritable<String>>,
+    #[serde(rename = "license-file")]
+    pub license_file: Option<Inheritable<String>>,
+    pub readme: Option<Inheritable<Readme>>,
+    pub repository: Option<Inheritable<String>>,
+    pub keywords: Option<Inheritable<Vec<String>>>,
+    pub categories: Option<Inheritable<Vec<String>>>,
+}
+
+/// Package field that may be inherited from `workspace.package`
+/// using `<field>.workspace = true`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Inheritable<T> {
+    Value(T),
+    Workspace { workspace: bool },
+}
+
+impl<T> Inheritable<T> {
+    /// Resolves the field against the value defined in `workspace.package`,
+    /// returning the value along with `true` if it has been inherited,
+    /// `None` if the field is inherited but not defined by the workspace.
+    pub fn resolve<'a>(&'a self, workspace: Option<&'a T>) -> Option<(&'a T, bool)> {
+        match self {
+            Inheritable::Value(value) => Some((value, false)),
+            Inheritable::Workspace { workspace: true } => workspace.map(|value| (value, true)),
+            Inheritable::Workspace { workspace: false } => None,
+        }
+    }
+}
+
+/// `package.readme`: path to the README file or `false` to disable it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Readme {
+    Path(String),
+    Enabled(bool),
+}
+
+/// `package.publish`: <https://doc.rust-lang.org/cargo/reference/manifest.html#the-publish-field>
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Publish {
+    Enabled(bool),
+    /// registries the crate may be published to
+    Registries(Vec<String>),
+    /// `publish.workspace = true`
+    Workspace {
+        workspace: bool,
+    },
+}
+
+impl Package {
+    /// Returns `false` if publishing is disabled using `publish = false` or `publish = []`.
+    pub fn is_published(&self) -> bool {
+        match &self.publish {
+            Some(Publish::Enabled(enabled)) => *enabled,
+            Some(Publish::Registries(registries)) => !registries.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// Registries listed in `package.publish`, `None` if not restricted.
+    pub fn registries(&self) -> Option<&[String]> {
+        match &self.publish {
+            Some(Publish::Registries(registries)) => Some(registries),
+            _ => None,
+        }
+    }
+
+    /// Explicit `package.version`, `None` if inherited
+    /// from the workspace using `version.workspace = true`.
+    pub fn explicit_version(&self) -> Result<Option<Version>> {
+        match &self.version {
+            Value::String(version) => Ok(Some(version.parse()?)),
+            _ => Ok(None),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -125,6 +241,14 @@ impl Manifest {
     pub fn version(&self) -> Result<Version> {
         self.workspace.package.version.parse()
     }
+
+    pub fn metadata(&self) -> Result<Option<WorkspaceMetadata>> {
+        if let Some(Some(emanate)) = self.workspace.metadata.as_ref().map(|m| m.get("emanate")) {
+            let metadata = WorkspaceMetadata::deserialize(emanate.clone())?;
+            return Ok(Some(metadata));
+        }
+        Ok(None)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -132,13 +256,144 @@ pub struct Workspace {
     pub members: Vec<String>,
     pub package: WorkspacePackage,
     pub dependencies: Dependencies,
+    pub metadata: Option<Value>,
+}
+
+/// Workspace configuration specified within `workspace.metadata.emanate`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorkspaceMetadata {
+    pub changelog: Option<ChangelogMetadata>,
+    pub git: Option<GitMetadata>,
+    pub version: Option<VersionMetadata>,
+    pub publish: Option<PublishMetadata>,
+}
+
+/// Version propagation configuration (`workspace.metadata.emanate.version`
+/// or `package.metadata.emanate.version` for a standalone crate)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VersionMetadata {
+    /// non-Cargo files containing the version
+    pub files: Option<Vec<VersionFile>>,
+}
+
+/// Non-Cargo file (`package.json`, `README.md` etc.) containing a version string
+#[derive(Debug, Clone, Deserialize)]
+pub struct VersionFile {
+    /// path glob relative to the workspace folder
+    pub path: String,
+    /// regular expression whose first capture group is replaced with the new version
+    pub pattern: String,
+    /// independently versioned crate the version belongs to (default: workspace version)
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+}
+
+/// Changelog configuration (`workspace.metadata.emanate.changelog`
+/// or `package.metadata.emanate.changelog` for a standalone crate)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChangelogMetadata {
+    /// maintain a changelog in the workspace folder (default: `true`)
+    pub workspace: Option<bool>,
+    /// maintain changelogs in member crate folders (default: `false`)
+    pub crates: Option<bool>,
+    /// changelog file name (default: `CHANGELOG.md`)
+    pub file: Option<String>,
+}
+
+/// Publishing configuration (`workspace.metadata.emanate.publish`
+/// or `package.metadata.emanate.publish` for a standalone crate)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PublishMetadata {
+    /// sparse registry index (default: `https://index.crates.io/`)
+    pub index: Option<String>,
+    /// seconds to wait for a published version to appear in the index (default: `300`)
+    pub timeout: Option<u64>,
+    /// seconds between index polls (default: `5`)
+    pub poll_interval: Option<u64>,
+    /// maximum number of crates uploaded concurrently (default: `4`)
+    pub parallel: Option<usize>,
+}
+
+impl PublishMetadata {
+    pub fn index(&self) -> &str {
+        self.index.as_deref().unwrap_or("https://index.crates.io/")
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.timeout.unwrap_or(300))
+    }
+
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.poll_interval.unwrap_or(5))
+    }
+
+    pub fn parallel(&self) -> usize {
+        self.parallel.unwrap_or(4).max(1)
+    }
+}
+
+/// Release commit and tag configuration (`workspace.metadata.emanate.git`
+/// or `package.metadata.emanate.git` for a standalone crate)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GitMetadata {
+    /// tag template for the workspace version (default: `v{version}`),
+    /// creates a tag for each crate if containing `{crate}`
+    pub tag: Option<String>,
+    /// tag template for independently versioned crates (default: `{crate}-v{version}`)
+    pub crate_tag: Option<String>,
+    /// commit message template (default: `chore: release {version}`)
+    pub message: Option<String>,
+}
+
+impl GitMetadata {
+    /// Tag template for the workspace version or, if `independent`,
+    /// for an independently versioned crate.
+    pub fn tag_template(&self, independent: bool) -> &str {
+        if independent {
+            self.crate_tag.as_deref().unwrap_or("{crate}-v{version}")
+        } else {
+            self.tag.as_deref().unwrap_or("v{version}")
+        }
+    }
+
+    pub fn tag(&self, independent: bool, name: &str, version: &str) -> String {
+        self.tag_template(independent)
+            .replace("{crate}", name)
+            .replace("{version}", version)
+    }
+
+    /// Glob pattern matching release tags of the workspace
+    /// or of the given independently versioned crate.
+    pub fn tag_pattern(&self, independent: Option<&str>) -> String {
+        self.tag_template(independent.is_some())
+            .replace("{crate}", independent.unwrap_or("*"))
+            .replace("{version}", "*")
+    }
+
+    pub fn message(&self, version: &str) -> String {
+        self.message
+            .as_deref()
+            .unwrap_or("chore: release {version}")
+            .replace("{version}", version)
+    }
 }
 
 pub type Dependencies = HashMap<String, Dependency>;
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct WorkspacePackage {
     pub version: String,
+    pub publish: Option<Publish>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub license_file: Option<String>,
+    pub readme: Option<Readme>,
+    pub repository: Option<String>,
+    pub keywords: Option<Vec<String>>,
+    pub categories: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -154,6 +409,56 @@ impl Dependency {
             _ => None,
         }
     }
+    /// Registry of the dependency, `None` for crates.io.
+    pub fn registry(&self) -> Option<&str> {
+        match &self.0 {
+            Value::Table(table) => table.get("registry").and_then(Value::as_str),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the dependency specifies a version.
+    pub fn has_version(&self) -> bool {
+        match &self.0 {
+            Value::String(_) => true,
+            Value::Table(table) => table.contains_key("version"),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the dependency refers to a local crate using `path`.
+    pub fn is_path(&self) -> bool {
+        match &self.0 {
+            Value::Table(table) => table.contains_key("path"),
+            _ => false,
+        }
+    }
+
+    /// Name of the crate if the dependency is renamed using `package`.
+    pub fn package(&self) -> Option<&str> {
+        match &self.0 {
+            Value::Table(table) => table.get("package").and_then(Value::as_str),
+            _ => None,
+        }
+    }
+
+    /// Version requirement as declared, `None` if not specified.
+    pub fn requirement(&self) -> Option<&str> {
+        match &self.0 {
+            Value::String(s) => Some(s),
+            Value::Table(table) => table.get("version").and_then(Value::as_str),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the dependency is inherited from `workspace.dependencies`.
+    pub fn is_workspace(&self) -> bool {
+        match &self.0 {
+            Value::Table(table) => table.get("workspace").and_then(Value::as_bool) == Some(true),
+            _ => false,
+        }
+    }
+
     pub fn version(&self) -> Result<Version> {
         match &self.0 {
             Value::String(s) => Ok(s.parse().map_err(|err| error!("{err}"))?),
@@ -248,13 +553,13 @@ pub enum WasmTargetPlatform {
     NodeJs,
 }
 
-impl ToString for WasmTargetPlatform {
-    fn to_string(&self) -> String {
-        match self {
+impl std::fmt::Display for WasmTargetPlatform {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let platform = match self {
             WasmTargetPlatform::Web => "web",
             WasmTargetPlatform::NodeJs => "nodejs",
-        }
-        .to_string()
+        };
+        f.write_str(platform)
     }
 }
 
@@ -7,7 +7,7 @@ pub enum Action {
     Remove,
 }
 
-/// Executes `cargo owner` on all workspace crates: <https://doc.rust-lang.org/cargo/reference/publishing.html#cargo-owner>
+/// Executes `cargo owner` on all workspace crates (or a standalone crate): <https://doc.rust-lang.org/cargo/reference/publishing.html#cargo-owner>
 ///
 /// This handler executes the following commands:
 /// ```bash
@@ -16,46 +16,44 @@ pub enum Action {
 /// $ cargo owner --add github:rust-lang:owners
 /// $ cargo owner --remove github:rust-lang:owners
 /// ```
-
 pub struct Owner {
     ctx: Context,
+    /// registry requested using `--registry`
+    registry: Option<String>,
 }
 
 impl Owner {
-    pub fn new(ctx: Context) -> Self {
-        Self { ctx }
+    pub fn new(ctx: Context, registry: Option<String>) -> Self {
+        Self { ctx, registry }
     }
 
     pub async fn change(&self, action: Action, username: String) -> Result<()> {
-        match &self.ctx {
-            Context::Workspace(ctx) => {
-                for crt in ctx.crates.iter() {
-                    let project = &crt.name().to_string();
+        let registries = self.ctx.registries(self.registry.as_deref())?;
+        for crt in self.ctx.crates() {
+            let project = &crt.name().to_string();
+            let Some(registry) = registries.get(project) else {
+                continue;
+            };
 
-                    let (action, descr) = match action {
-                        Action::Add => ("--add", "adding"),
-                        Action::Remove => ("--remove", "removing"),
-                    };
+            let (action, descr) = match action {
+                Action::Add => ("--add", "adding"),
+                Action::Remove => ("--remove", "removing"),
+            };
 
-                    let result = cmd!("cargo", "owner", action, &username)
-                        .dir(&crt.folder)
-                        .run();
+            let mut args = vec!["owner", action, &username];
+            args.extend(registry.args());
+            let result = cmd("cargo", args).dir(&crt.folder).run();
 
-                    match result {
-                        Ok(_) => {
-                            log_info!("Owner", "{project} -> {descr} {username}");
-                        }
-                        Err(err) => {
-                            log_warn!("Owner", "{project} -> {err}");
-                            // println!("\n{err}\n");
-                            // println!("\t->  {project}\n");
-                            // return Ok(());
-                        }
-                    }
+            match result {
+                Ok(_) => {
+                    log_info!("Owner", "{project} -> {descr} {username} ({registry})");
+                }
+                Err(err) => {
+                    log_warn!("Owner", "{project} -> {err}");
+                    // println!("\n{err}\n");
+                    // println!("\t->  {project}\n");
+                    // return Ok(());
                 }
-            }
-            _ => {
-                panic!("not currently supported in the context of a single crate");
             }
         }
 
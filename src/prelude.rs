@@ -1,11 +1,13 @@
 pub use crate::archive::*;
+pub use crate::audit::*;
 pub use crate::error::error;
 pub use crate::error::Error;
 pub use crate::result::Result;
 pub use crate::utils::*;
 pub use crate::{
-    build::*, check::*, context::*, crates::*, log::*, manifest::*, owner::*, publish::*,
-    version::*,
+    build::*, changelog::*, check::*, commit::*, context::*, crates::*, git::*, index::*,
+    journal::*, log::*, manifest::*, owner::*, publish::*, registry::*, validate::*, version::*,
+    yank::*,
 };
 pub use console::style;
 pub use duct::cmd;
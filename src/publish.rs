@@ -1,94 +1,431 @@
 use crate::prelude::*;
+use futures_util::stream::{self, StreamExt};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 pub struct Publisher {
     ctx: Context,
+    /// registry requested using `--registry`
+    registry: Option<String>,
 }
 
 impl Publisher {
-    pub fn new(ctx: Context) -> Self {
-        Self { ctx }
+    pub fn new(ctx: Context, registry: Option<String>) -> Self {
+        Self { ctx, registry }
     }
 
-    pub async fn publish(&self, dry_run: bool) -> Result<()> {
-        match &self.ctx {
-            Context::Workspace(ctx) => {
-                let crates_io = CratesIo::new();
-                let manifest_version = ctx.manifest.version()?;
-
-                let mut new_publish_list = HashMap::new();
-
-                for crt in ctx.crates.iter() {
-                    let project = &crt.name().to_string();
-                    let version = crates_io.get_latest_version(project).await?;
-
-                    if version == manifest_version {
-                        log_info!("Skipping", "{project} {manifest_version} -> {version}");
-                    } else {
-                        log_info!("Publishing", "{project} {version} -> {manifest_version}");
-                        if dry_run {
-                            if crt.dependencies.is_empty() {
-                                let key = format!("{project}/{manifest_version}");
-                                new_publish_list.insert(key, version.to_string());
-                                continue;
-                            }
-                            log_info!("Dependencies", "");
-                            for (dep, dep_info) in &crt.dependencies {
-                                if let Some(v) = dep_info.find_version(dep, ctx) {
-                                    let key = format!("{dep}/{v}");
-                                    if !new_publish_list.contains_key(&key) {
-                                        log_error!("Error", "{dep} => unable to find {dep}/{v}");
-                                    } else {
-                                        log_info!("", "{dep} => {v}");
-                                    }
-                                } else {
-                                    log_error!("Error", "{dep} => unable to get version");
-                                }
-                            }
-
-                            //println!("crt.dev_dependencies: {:?}", crt.dev_dependencies);
-                            for (dep, dep_info) in &crt.dev_dependencies {
-                                if let Some(v) = dep_info.find_version(dep, ctx) {
-                                    let key = format!("{dep}/{v}");
-                                    if !new_publish_list.contains_key(&key) {
-                                        log_error!(
-                                            "Error",
-                                            "dev:{dep} => unable to find {dep}/{v}"
-                                        );
-                                    } else {
-                                        log_info!("", "dev:{dep} => {v}");
-                                    }
-                                } else {
-                                    log_error!("Error", "dev:{dep} => unable to get version");
-                                }
-                            }
+    pub async fn publish(&self, dry_run: bool, resume: bool) -> Result<()> {
+        let config = self.ctx.publish()?;
+        let registries = self.ctx.registries(self.registry.as_deref())?;
+        if registries.is_empty() {
+            log_info!("Publish", "nothing to publish");
+            return Ok(());
+        }
+        Validator::new(&self.ctx).validate(&registries)?;
 
-                            let key = format!("{project}/{manifest_version}");
-                            new_publish_list.insert(key, version.to_string());
+        // registry clients and indexes keyed by the registry name
+        let mut clients = HashMap::new();
+        for registry in registries.values() {
+            if !clients.contains_key(&registry.name) {
+                let index = if registry.is_crates_io() {
+                    RegistryIndex::new(config.index())
+                } else {
+                    RegistryIndex::for_registry(registry)?
+                };
+                let client = CratesIo::with_registry(registry)?;
+                clients.insert(registry.name.clone(), (client, index));
+            }
+        }
+
+        let mut new_publish_list = HashMap::new();
+        // crates packaged in the dry-run mode and whether they are to be uploaded
+        let mut packages = vec![];
+
+        // progress is not recorded in the dry-run mode
+        let journal = if dry_run {
+            None
+        } else if resume {
+            Some(Arc::new(PublishJournal::load(
+                &self.ctx,
+                &self.ctx.version()?,
+            )?))
+        } else {
+            let journal = PublishJournal::new(&self.ctx, &self.ctx.version()?)?;
+            journal.store()?;
+            Some(Arc::new(journal))
+        };
+
+        let layers = self.ctx.layers();
+        for (idx, layer) in layers.iter().enumerate() {
+            if layers.len() > 1 {
+                let names = layer.iter().map(|crt| crt.name()).collect::<Vec<_>>();
+                log_info!(
+                    "Layer",
+                    "{}/{}: {}",
+                    idx + 1,
+                    layers.len(),
+                    names.join(", ")
+                );
+            }
+
+            // crates to upload and crates awaiting the index propagation
+            let mut uploads = vec![];
+            let mut uploaded = vec![];
+
+            for crt in layer.iter() {
+                let project = &crt.name().to_string();
+                let manifest_version = self.ctx.crate_version(crt)?;
 
+                let Some(registry) = registries.get(project) else {
+                    log_info!(
+                        "Skipping",
+                        "{project} {manifest_version} - not published to `{}`",
+                        self.registry.as_deref().unwrap_or_default()
+                    );
+                    continue;
+                };
+                let (crates_io, _) = &clients[&registry.name];
+
+                if let Some(journal) = journal.as_ref() {
+                    match journal.state(project) {
+                        PublishState::Visible => {
+                            log_info!(
+                                "Skipping",
+                                "{project} {manifest_version} - already published"
+                            );
+                            continue;
+                        }
+                        PublishState::Uploaded => {
+                            uploaded.push((project.clone(), manifest_version));
                             continue;
                         }
+                        _ => {}
+                    }
+                }
 
-                        let result = cmd!("cargo", "publish", "--package", project)
-                            .dir(&ctx.folder)
-                            .run();
-                        match result {
-                            Ok(_) => {
-                                log_info!("Success", "published {project} @ {manifest_version}");
-                            }
-                            Err(err) => {
-                                println!("\n{err}\n");
-                                println!("\t->  {project}\n");
-                                return Ok(());
-                            }
+                // crates not yet present in the registry are published for the first time
+                let published = match crates_io.get_crate(project).await? {
+                    Some(published) if published.name != *project => {
+                        return Err(error!(
+                            "unable to publish `{project}`: the name is already taken by `{}` in `{registry}`",
+                            published.name
+                        ));
+                    }
+                    Some(published) => Some(published),
+                    None => {
+                        log_warn!(
+                            "New",
+                            "{project} is not present in `{registry}`, the name is available and will be published for the first time"
+                        );
+                        None
+                    }
+                };
+                let version = published
+                    .as_ref()
+                    .and_then(|published| published.latest())
+                    .map(|version| version.to_string())
+                    .unwrap_or("none".to_string());
+
+                if published
+                    .as_ref()
+                    .is_some_and(|published| published.contains(&manifest_version))
+                {
+                    log_info!("Skipping", "{project} {manifest_version} -> {version}");
+                    packages.push((project.clone(), manifest_version.clone(), false));
+                    let key = format!("{project}/{manifest_version}");
+                    new_publish_list.insert(key, version.clone());
+                    if let Some(journal) = journal.as_ref() {
+                        journal.set_state(project, PublishState::Visible)?;
+                    }
+                } else {
+                    log_info!("Publishing", "{project} {version} -> {manifest_version}");
+                    if dry_run {
+                        packages.push((project.clone(), manifest_version.clone(), true));
+
+                        // internal dependencies are only present in a workspace
+                        let Context::Workspace(ctx) = &self.ctx else {
+                            continue;
+                        };
+
+                        if crt.dependencies.is_empty() && crt.build_dependencies.is_empty() {
+                            let key = format!("{project}/{manifest_version}");
+                            new_publish_list.insert(key, version.clone());
+                            continue;
                         }
+                        log_info!("Dependencies", "");
+                        check_references(&crt.dependencies, "", ctx, &new_publish_list);
+                        check_references(&crt.build_dependencies, "build:", ctx, &new_publish_list);
+                        check_references(&crt.dev_dependencies, "dev:", ctx, &new_publish_list);
+
+                        let key = format!("{project}/{manifest_version}");
+                        new_publish_list.insert(key, version.clone());
+
+                        continue;
+                    }
+
+                    uploads.push((project.clone(), manifest_version));
+                }
+            }
+
+            let Some(journal) = journal.as_ref() else {
+                continue;
+            };
+
+            let mut failures = vec![];
+            for (project, version, result) in self
+                .upload(journal, &registries, uploads, config.parallel())
+                .await
+            {
+                match result {
+                    Ok(()) => uploaded.push((project, version)),
+                    Err(err) => {
+                        journal.set_error(&project, err.to_string())?;
+                        failures.push(format!("`{project}`: {err}"));
                     }
                 }
             }
-            _ => {
-                panic!("not currently supported in the context of a single crate");
+            if !failures.is_empty() {
+                journal.report();
+                return Err(error!(
+                    "failed to publish {}\nrun `cargo emanate publish --resume` to continue",
+                    failures.join(", ")
+                ));
             }
+
+            for (project, version) in uploaded {
+                let (_, index) = &clients[&registries[&project].name];
+                self.confirm(index, &config, journal, &project, &version)
+                    .await?;
+            }
+        }
+
+        if dry_run {
+            return self.package(&registries, &packages);
         }
 
+        if let Some(journal) = journal {
+            journal.report();
+        }
+
+        Ok(())
+    }
+
+    /// Packages and verifies the crates without uploading them, printing
+    /// a pass/fail summary along with cargo diagnostics of failed crates.
+    fn package(
+        &self,
+        registries: &HashMap<String, Registry>,
+        crates: &[(String, Version, bool)],
+    ) -> Result<()> {
+        // crates not yet published are resolved from their local sources
+        let mut patches = vec![];
+        if let Context::Workspace(ctx) = &self.ctx {
+            for crt in ctx.crates.iter() {
+                let registry = match registries.get(crt.name()) {
+                    Some(registry) => registry.to_string(),
+                    None => crt
+                        .package
+                        .registries()
+                        .and_then(|registries| registries.first().cloned())
+                        .unwrap_or("crates-io".to_string()),
+                };
+                let path = toml::Value::String(crt.folder.to_string_lossy().to_string());
+                patches.push((
+                    crt.name(),
+                    format!("patch.{registry}.{}.path={path}", crt.name()),
+                ));
+            }
+        }
+
+        let mut results = vec![];
+        for (project, version, upload) in crates.iter() {
+            log_state!("Packaging", "{project} {version}");
+            let mut args = vec!["package", "--package", project, "--allow-dirty"];
+            args.extend(registries[project].args());
+            for (name, patch) in patches.iter() {
+                if name != project {
+                    args.extend(["--config", patch.as_str()]);
+                }
+            }
+            let output = cmd("cargo", args)
+                .dir(self.ctx.folder())
+                .stdout_capture()
+                .stderr_capture()
+                .unchecked()
+                .run()?;
+            log_state_clear();
+            let diagnostics = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            results.push((
+                project,
+                version,
+                upload,
+                output.status.success(),
+                diagnostics,
+            ));
+        }
+
+        let len = crates
+            .iter()
+            .map(|(project, _, _)| project.len())
+            .fold("crate".len(), |a, b| a.max(b));
+        let vlen = crates
+            .iter()
+            .map(|(_, version, _)| version.to_string().len())
+            .fold("version".len(), |a, b| a.max(b));
+        println!();
+        println!(
+            "{}",
+            style(format!(
+                "{} {} {:<9} result",
+                "crate".pad(len, ' ', Alignment::Left, false),
+                "version".pad(vlen, ' ', Alignment::Left, false),
+                "upload"
+            ))
+            .bold()
+        );
+        for (project, version, upload, success, _) in results.iter() {
+            let row = format!(
+                "{} {} {:<9}",
+                project.pad(len, ' ', Alignment::Left, false),
+                version.to_string().pad(vlen, ' ', Alignment::Left, false),
+                if **upload { "yes" } else { "published" }
+            );
+            if *success {
+                println!("{row} {}", style("pass").green());
+            } else {
+                println!("{row} {}", style("fail").red());
+            }
+        }
+        println!();
+
+        let failed = results
+            .iter()
+            .filter(|(_, _, _, success, _)| !success)
+            .collect::<Vec<_>>();
+        for (project, version, _, _, diagnostics) in failed.iter() {
+            log_error!("Diagnostics", "{project} {version}");
+            for line in diagnostics.lines() {
+                println!("             {line}");
+            }
+            println!();
+        }
+
+        if failed.is_empty() {
+            log_info!("Dry-run", "{} crates packaged successfully", results.len());
+            Ok(())
+        } else {
+            Err(error!(
+                "{} of {} crates failed to package",
+                failed.len(),
+                results.len()
+            ))
+        }
+    }
+
+    /// Packages and uploads the crates, running at most `parallel` uploads
+    /// concurrently. Once an upload fails, the remaining crates are not
+    /// started while the uploads in progress are allowed to complete.
+    async fn upload(
+        &self,
+        journal: &Arc<PublishJournal>,
+        registries: &HashMap<String, Registry>,
+        crates: Vec<(String, Version)>,
+        parallel: usize,
+    ) -> Vec<(String, Version, Result<()>)> {
+        let failed = Arc::new(AtomicBool::new(false));
+        stream::iter(crates)
+            .map(|(project, version)| {
+                let folder = self.ctx.folder().clone();
+                let journal = journal.clone();
+                let registry = registries[&project].clone();
+                let failed = failed.clone();
+                async move {
+                    if failed.load(Ordering::SeqCst) {
+                        return None;
+                    }
+                    let name = project.clone();
+                    let result = tokio::task::spawn_blocking(move || {
+                        upload(&folder, &journal, &registry, &name)
+                    })
+                    .await
+                    .unwrap_or_else(|err| Err(error!("{err}")));
+                    if result.is_err() {
+                        failed.store(true, Ordering::SeqCst);
+                    }
+                    Some((project, version, result))
+                }
+            })
+            .buffer_unordered(parallel)
+            .filter_map(|result| async move { result })
+            .collect()
+            .await
+    }
+
+    /// Waits for the published version to appear in the registry index,
+    /// so that crates depending on it can be published.
+    async fn confirm(
+        &self,
+        index: &RegistryIndex,
+        config: &PublishMetadata,
+        journal: &PublishJournal,
+        project: &str,
+        version: &Version,
+    ) -> Result<()> {
+        let visible = index
+            .wait(project, version, config.timeout(), config.poll_interval())
+            .await?;
+        if !visible {
+            journal.report();
+            return Err(error!(
+                "{project} {version} did not appear in the registry index within {}s\nrun `cargo emanate publish --resume` to continue",
+                config.timeout().as_secs()
+            ));
+        }
+
+        journal.set_state(project, PublishState::Visible)?;
+        log_info!("Success", "published {project} @ {version}");
         Ok(())
     }
 }
+
+/// Packages and uploads the crate, recording the progress in the journal.
+fn upload(
+    folder: &Path,
+    journal: &PublishJournal,
+    registry: &Registry,
+    project: &str,
+) -> Result<()> {
+    let mut args = vec!["package", "--package", project];
+    args.extend(registry.args());
+    cmd("cargo", &args).dir(folder).run()?;
+    journal.set_state(project, PublishState::Packaged)?;
+
+    // the package has been verified above
+    let mut args = vec!["publish", "--package", project, "--no-verify"];
+    args.extend(registry.args());
+    cmd("cargo", &args).dir(folder).run()?;
+    journal.set_state(project, PublishState::Uploaded)?;
+
+    Ok(())
+}
+
+/// Reports dependencies referencing versions that are not going to be
+/// available once the preceding crates are published (dry-run only).
+fn check_references(
+    dependencies: &Dependencies,
+    label: &str,
+    ctx: &Arc<WorkspaceContext>,
+    publish_list: &HashMap<String, String>,
+) {
+    for (dep, dep_info) in dependencies {
+        if let Some(v) = dep_info.find_version(dep, ctx) {
+            let key = format!("{dep}/{v}");
+            if !publish_list.contains_key(&key) {
+                log_error!("Error", "{label}{dep} => unable to find {dep}/{v}");
+            } else {
+                log_info!("", "{label}{dep} => {v}");
+            }
+        } else {
+            log_error!("Error", "{label}{dep} => unable to get version");
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use toml::Value;
+
+/// Package registry, either crates.io or an alternate registry
+/// configured in `.cargo/config.toml`:
+/// <https://doc.rust-lang.org/cargo/reference/registries.html>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Registry {
+    /// registry name, `None` for crates.io
+    pub name: Option<String>,
+    /// index URL (`sparse+https://...` for sparse registries)
+    pub index: String,
+    /// token used to access registries requiring authentication
+    pub token: Option<String>,
+}
+
+impl std::fmt::Display for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name.as_deref().unwrap_or("crates-io"))
+    }
+}
+
+impl Registry {
+    pub fn crates_io() -> Registry {
+        Registry {
+            name: None,
+            index: "sparse+https://index.crates.io/".to_string(),
+            token: None,
+        }
+    }
+
+    pub fn is_crates_io(&self) -> bool {
+        self.name.is_none()
+    }
+
+    /// Resolves the registry with the given name (crates.io if `None`
+    /// or `crates-io`) using the cargo configuration visible from the folder.
+    pub fn resolve(folder: &Path, name: Option<&str>) -> Result<Registry> {
+        let Some(name) = name.filter(|name| *name != "crates-io") else {
+            return Ok(Registry::crates_io());
+        };
+
+        let var = name.to_uppercase().replace('-', "_");
+        let configs = cargo_config(folder)?;
+        let setting = |key: &str| {
+            std::env::var(format!("CARGO_REGISTRIES_{var}_{}", key.to_uppercase()))
+                .ok()
+                .or_else(|| {
+                    configs.iter().find_map(|config| {
+                        config
+                            .get("registries")
+                            .and_then(|registries| registries.get(name))
+                            .and_then(|registry| registry.get(key))
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                    })
+                })
+        };
+
+        let index = setting("index").ok_or_else(|| {
+            error!("registry `{name}` is not configured: `registries.{name}.index` not found in `.cargo/config.toml`")
+        })?;
+
+        Ok(Registry {
+            name: Some(name.to_string()),
+            index,
+            token: setting("token"),
+        })
+    }
+
+    /// Registry used when none is specified: `registry.default`
+    /// of the cargo configuration or crates.io.
+    pub fn default_registry(folder: &Path) -> Result<Registry> {
+        let name = std::env::var("CARGO_REGISTRY_DEFAULT").ok().or_else(|| {
+            cargo_config(folder).ok()?.iter().find_map(|config| {
+                config
+                    .get("registry")
+                    .and_then(|registry| registry.get("default"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+        });
+        Registry::resolve(folder, name.as_deref())
+    }
+
+    /// Registry the crate is published to: the requested registry or the only
+    /// registry listed in `package.publish`, `None` if the crate can not be
+    /// published to the requested registry.
+    pub fn select(folder: &Path, crt: &Crate, requested: Option<&str>) -> Result<Option<Registry>> {
+        let registries = crt.package.registries();
+        match (requested, registries) {
+            (Some(requested), Some(registries))
+                if !registries.iter().any(|registry| registry == requested) =>
+            {
+                Ok(None)
+            }
+            (Some(requested), _) => Ok(Some(Registry::resolve(folder, Some(requested))?)),
+            (None, Some([registry])) => Ok(Some(Registry::resolve(folder, Some(registry))?)),
+            (None, Some(registries)) => Err(error!(
+                "`{}` can be published to multiple registries ({}), please specify --registry",
+                crt.name(),
+                registries.join(", ")
+            )),
+            (None, None) => Ok(Some(Registry::default_registry(folder)?)),
+        }
+    }
+
+    /// Sparse index URL, used to query the registry.
+    pub fn sparse_index(&self) -> Result<&str> {
+        self.index.strip_prefix("sparse+").ok_or_else(|| {
+            error!(
+                "registry `{self}` uses a git index `{}`, only sparse registries can be queried",
+                self.index
+            )
+        })
+    }
+
+    /// Arguments selecting the registry for cargo commands.
+    pub fn args(&self) -> Vec<&str> {
+        match self.name.as_deref() {
+            Some(name) => vec!["--registry", name],
+            None => vec![],
+        }
+    }
+}
+
+/// Cargo configuration files in the order of precedence: `.cargo/config.toml`
+/// in the folder and its parents followed by the one in `$CARGO_HOME`,
+/// with the registry tokens from `$CARGO_HOME/credentials.toml`.
+fn cargo_config(folder: &Path) -> Result<Vec<Value>> {
+    let mut files = folder
+        .ancestors()
+        .map(|folder| folder.join(".cargo"))
+        .collect::<Vec<_>>();
+    if let Ok(home) = home::cargo_home() {
+        if !files.contains(&home) {
+            files.push(home.clone());
+        }
+        files.push(home.join("credentials.toml"));
+        files.push(home.join("credentials"));
+    }
+
+    let mut configs = vec![];
+    for file in files {
+        let file = if file.is_dir() {
+            match ["config.toml", "config"]
+                .iter()
+                .map(|name| file.join(name))
+                .find(|file| file.is_file())
+            {
+                Some(file) => file,
+                None => continue,
+            }
+        } else if file.is_file() {
+            file
+        } else {
+            continue;
+        };
+        let toml = fs::read_to_string(&file)?;
+        let config: Value = toml::from_str(&toml)
+            .map_err(|err| error!("unable to parse `{}`: {err}", file.display()))?;
+        configs.push(config);
+    }
+
+    Ok(configs)
+}
@@ -0,0 +1,337 @@
+use crate::prelude::*;
+
+/// Keyword and category limits enforced by crates.io
+const MAX_KEYWORDS: usize = 5;
+const MAX_KEYWORD_LENGTH: usize = 20;
+const MAX_CATEGORIES: usize = 5;
+
+/// Files detected by cargo if `package.readme` is not specified
+const README_FILES: &[&str] = &["README.md", "README.txt", "README"];
+
+/// Checks the package metadata of all crates before publishing:
+/// - `description` and `license` or `license-file` are present,
+///   as required by crates.io
+/// - fields inherited from `workspace.package` are defined by the workspace
+/// - the README and license files exist
+/// - keywords and categories comply with the crates.io limits
+///
+/// Missing `repository`, README, `keywords` and `categories` are reported
+/// as warnings. Problems of crates published to alternate registries,
+/// which may not require the metadata, are reported as warnings as well.
+pub struct Validator<'a> {
+    ctx: &'a Context,
+}
+
+#[derive(Default)]
+struct Problems {
+    errors: Vec<Violation>,
+    warnings: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(ctx: &'a Context) -> Self {
+        Self { ctx }
+    }
+
+    /// Validates crates that are to be published to the given registries,
+    /// reporting all problems at once.
+    pub fn validate(&self, registries: &HashMap<String, Registry>) -> Result<()> {
+        let mut problems = Problems::default();
+        let mut crates = 0;
+        for crt in self.ctx.crates() {
+            let Some(registry) = registries.get(crt.name()) else {
+                continue;
+            };
+            crates += 1;
+            self.validate_crate(crt, registry.is_crates_io(), &mut problems);
+        }
+
+        for Violation { file, key, message } in problems.warnings.iter() {
+            log_warn!("Metadata", "{} `{key}`: {message}", file.display());
+        }
+        for Violation { file, key, message } in problems.errors.iter() {
+            log_error!("Metadata", "{} `{key}`: {message}", file.display());
+        }
+
+        if problems.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(error!(
+                "{} metadata problems found in {crates} crates, nothing has been published",
+                problems.errors.len()
+            ))
+        }
+    }
+
+    fn validate_crate(&self, crt: &Crate, required: bool, problems: &mut Problems) {
+        let package = &crt.package;
+        let (workspace, workspace_folder) = match self.ctx {
+            Context::Workspace(ctx) => (Some(&ctx.manifest.workspace.package), &ctx.folder),
+            Context::Crate(ctx) => (None, &ctx.folder),
+        };
+
+        let mut problem = |key: &str, message: String, required: bool| {
+            let violation = Violation {
+                file: crt.file.clone(),
+                key: format!("package.{key}"),
+                message,
+            };
+            if required {
+                problems.errors.push(violation);
+            } else {
+                problems.warnings.push(violation);
+            }
+        };
+
+        // resolves inherited fields, reporting those missing from the workspace
+        // as `Err(())` so that they are not reported as unspecified again
+        macro_rules! resolve {
+            ($field:ident) => {
+                match package.$field.as_ref().map(|field| {
+                    field.resolve(workspace.and_then(|workspace| workspace.$field.as_ref()))
+                }) {
+                    Some(Some(resolved)) => Ok(Some(resolved)),
+                    Some(None) => {
+                        let key = stringify!($field).replace('_', "-");
+                        problem(
+                            &key,
+                            format!(
+                                "inherited from `workspace.package` which does not define `{key}`"
+                            ),
+                            true,
+                        );
+                        Err(())
+                    }
+                    None => Ok(None),
+                }
+            };
+        }
+
+        match resolve!(description) {
+            Ok(Some((description, _))) if !description.trim().is_empty() => {}
+            Err(()) => {}
+            Ok(_) => problem(
+                "description",
+                "description is required by crates.io".to_string(),
+                required,
+            ),
+        }
+
+        let license = resolve!(license);
+        let license_file = resolve!(license_file);
+        if let Ok(Some((file, inherited))) = license_file {
+            let folder = if inherited {
+                workspace_folder
+            } else {
+                &crt.folder
+            };
+            if !folder.join(file).is_file() {
+                problem("license-file", format!("file `{file}` not found"), true);
+            }
+        } else if matches!((license, license_file), (Ok(None), Ok(None))) {
+            problem(
+                "license",
+                "`license` or `license-file` is required by crates.io".to_string(),
+                required,
+            );
+        }
+
+        match resolve!(readme) {
+            Ok(Some((Readme::Enabled(false), _))) | Err(()) => {}
+            Ok(Some((readme, inherited))) => {
+                let file = match readme {
+                    Readme::Path(file) => file.as_str(),
+                    _ => "README.md",
+                };
+                let folder = if inherited {
+                    workspace_folder
+                } else {
+                    &crt.folder
+                };
+                if !folder.join(file).is_file() {
+                    problem("readme", format!("file `{file}` not found"), true);
+                }
+            }
+            Ok(None) => {
+                if !README_FILES
+                    .iter()
+                    .any(|file| crt.folder.join(file).is_file())
+                {
+                    problem(
+                        "readme",
+                        "no README file found in the crate folder".to_string(),
+                        false,
+                    );
+                }
+            }
+        }
+
+        if let Ok(None) = resolve!(repository) {
+            problem(
+                "repository",
+                "repository is not specified".to_string(),
+                false,
+            );
+        }
+
+        match resolve!(keywords) {
+            Ok(Some((keywords, _))) if !keywords.is_empty() => {
+                if keywords.len() > MAX_KEYWORDS {
+                    problem(
+                        "keywords",
+                        format!(
+                            "{} keywords specified, crates.io accepts at most {MAX_KEYWORDS}",
+                            keywords.len()
+                        ),
+                        required,
+                    );
+                }
+                for keyword in keywords.iter().filter(|keyword| !is_valid_keyword(keyword)) {
+                    problem(
+                        "keywords",
+                        format!("invalid keyword `{keyword}`: keywords must start with a letter or a digit, contain only letters, digits, `_`, `-` or `+` and be at most {MAX_KEYWORD_LENGTH} characters long"),
+                        required,
+                    );
+                }
+            }
+            Err(()) => {}
+            Ok(_) => problem("keywords", "no keywords specified".to_string(), false),
+        }
+
+        match resolve!(categories) {
+            Ok(Some((categories, _))) if !categories.is_empty() => {
+                if categories.len() > MAX_CATEGORIES {
+                    problem(
+                        "categories",
+                        format!(
+                            "{} categories specified, crates.io accepts at most {MAX_CATEGORIES}",
+                            categories.len()
+                        ),
+                        required,
+                    );
+                }
+                for category in categories
+                    .iter()
+                    .filter(|category| !CATEGORIES.contains(&category.as_str()))
+                {
+                    problem(
+                        "categories",
+                        format!(
+                            "unknown category `{category}`, see https://crates.io/category_slugs"
+                        ),
+                        required,
+                    );
+                }
+            }
+            Err(()) => {}
+            Ok(_) => problem("categories", "no categories specified".to_string(), false),
+        }
+    }
+}
+
+fn is_valid_keyword(keyword: &str) -> bool {
+    keyword.len() <= MAX_KEYWORD_LENGTH
+        && keyword
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphanumeric())
+        && keyword
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+'))
+}
+
+/// Category slugs accepted by crates.io: <https://crates.io/category_slugs>
+const CATEGORIES: &[&str] = &[
+    "accessibility",
+    "aerospace",
+    "aerospace::drones",
+    "aerospace::protocols",
+    "aerospace::simulation",
+    "aerospace::space-protocols",
+    "aerospace::unmanned-aerial-vehicles",
+    "algorithms",
+    "api-bindings",
+    "asynchronous",
+    "authentication",
+    "caching",
+    "command-line-interface",
+    "command-line-utilities",
+    "compilers",
+    "compression",
+    "computer-vision",
+    "concurrency",
+    "config",
+    "cryptography",
+    "cryptography::cryptocurrencies",
+    "data-structures",
+    "database",
+    "database-implementations",
+    "date-and-time",
+    "development-tools",
+    "development-tools::build-utils",
+    "development-tools::cargo-plugins",
+    "development-tools::debugging",
+    "development-tools::ffi",
+    "development-tools::procedural-macro-helpers",
+    "development-tools::profiling",
+    "development-tools::testing",
+    "email",
+    "embedded",
+    "emulators",
+    "encoding",
+    "external-ffi-bindings",
+    "filesystem",
+    "finance",
+    "game-development",
+    "game-engines",
+    "games",
+    "graphics",
+    "gui",
+    "hardware-support",
+    "internationalization",
+    "localization",
+    "mathematics",
+    "memory-management",
+    "multimedia",
+    "multimedia::audio",
+    "multimedia::encoding",
+    "multimedia::images",
+    "multimedia::video",
+    "network-programming",
+    "no-std",
+    "no-std::no-alloc",
+    "os",
+    "os::android-apis",
+    "os::freebsd-apis",
+    "os::linux-apis",
+    "os::macos-apis",
+    "os::unix-apis",
+    "os::windows-apis",
+    "parser-implementations",
+    "parsing",
+    "rendering",
+    "rendering::data-formats",
+    "rendering::engine",
+    "rendering::graphics-api",
+    "rust-patterns",
+    "science",
+    "science::bioinformatics",
+    "science::bioinformatics::genomics",
+    "science::bioinformatics::proteomics",
+    "science::bioinformatics::sequence-analysis",
+    "science::geo",
+    "science::neuroscience",
+    "science::robotics",
+    "simulation",
+    "template-engine",
+    "text-editors",
+    "text-processing",
+    "value-formatting",
+    "virtualization",
+    "visualization",
+    "wasm",
+    "web-programming",
+    "web-programming::http-client",
+    "web-programming::http-server",
+    "web-programming::websocket",
+];
@@ -1,16 +1,19 @@
 use crate::prelude::*;
+use regex::Regex;
 use std::cmp::Ordering;
 use std::fmt;
 use std::str::FromStr;
-use toml_edit::{value, Document};
-// use regex::Regex;
+use toml_edit::{value, Document, Item, TableLike};
 
 #[derive(Debug, Clone)]
 pub struct Version {
     pub major: u32,
     pub minor: u32,
     pub patch: u32,
+    /// pre-release identifiers (`alpha.1` in `1.0.0-alpha.1`)
     pub suffix: Option<String>,
+    /// build metadata (`build.5` in `1.0.0+build.5`), ignored for precedence
+    pub build: Option<String>,
 }
 
 impl Version {
@@ -21,44 +24,180 @@ impl Version {
             minor,
             patch,
             suffix: None,
+            build: None,
         })
     }
 
-    pub fn change(&mut self, change: &Change) {
+    pub fn change(&mut self, change: &Change) -> Result<()> {
+        if !matches!(change, Change::Custom(_)) {
+            self.build = None;
+        }
         match change {
             Change::Major => {
                 self.major += 1;
                 self.minor = 0;
                 self.patch = 0;
+                self.suffix = None;
             }
             Change::Minor => {
                 self.minor += 1;
                 self.patch = 0;
+                self.suffix = None;
+            }
+            Change::Patch => {
+                self.patch += 1;
+                self.suffix = None;
+            }
+            Change::Pre(None) => {
+                let suffix = self
+                    .suffix
+                    .as_ref()
+                    .ok_or_else(|| error!("`{self}` is not a pre-release version"))?;
+                self.suffix = Some(increment_counter(suffix));
+            }
+            Change::Pre(Some(channel)) => match self.suffix.as_ref() {
+                None => {
+                    self.patch += 1;
+                    self.suffix = Some(format!("{channel}.1"));
+                }
+                Some(suffix) => {
+                    let current = suffix.split('.').next().unwrap_or_default();
+                    match current.parse::<Channel>() {
+                        Ok(current) if current == *channel => {
+                            self.suffix = Some(increment_counter(suffix));
+                        }
+                        Ok(current) if current > *channel => {
+                            return Err(error!(
+                                "unable to move `{self}` back to the `{channel}` channel"
+                            ));
+                        }
+                        _ => {
+                            let previous = self.clone();
+                            self.suffix = Some(format!("{channel}.1"));
+                            if *self <= previous {
+                                return Err(error!(
+                                    "unable to move `{previous}` back to the `{channel}` channel"
+                                ));
+                            }
+                        }
+                    }
+                }
+            },
+            Change::PreBump(level, channel) => {
+                self.change(&Change::from(*level))?;
+                self.suffix = Some(format!("{channel}.1"));
+            }
+            Change::Release => {
+                if self.suffix.is_none() {
+                    return Err(error!("`{self}` is not a pre-release version"));
+                }
+                self.suffix = None;
+            }
+            Change::Auto => {
+                return Err("`auto` change must be resolved from the git history".into());
             }
-            Change::Patch => self.patch += 1,
             Change::Custom(v) => *self = v.clone(),
         }
+        Ok(())
+    }
+
+    /// Returns `true` if the other version is semver compatible with this one,
+    /// i.e. it can be used in place of this version as cargo's default `^`
+    /// requirement allows: the same major version, the same minor version
+    /// for `0.y.z` and the same patch version for `0.0.z`.
+    pub fn is_compatible(&self, other: &Version) -> bool {
+        match (self.major, self.minor) {
+            (0, 0) => other.major == 0 && other.minor == 0 && other.patch == self.patch,
+            (0, minor) => other.major == 0 && other.minor == minor,
+            (major, _) => other.major == major,
+        }
+    }
+}
+
+/// Increments the trailing numeric identifier of a pre-release suffix
+/// (`alpha.1` -> `alpha.2`), appending `.1` if the suffix has no counter.
+fn increment_counter(suffix: &str) -> String {
+    match suffix.rsplit_once('.') {
+        Some((prefix, counter)) if counter.parse::<u64>().is_ok() => {
+            format!("{prefix}.{}", counter.parse::<u64>().unwrap() + 1)
+        }
+        _ => format!("{suffix}.1"),
     }
 }
 
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
         if let Some(suffix) = &self.suffix {
-            write!(f, "{}.{}.{}-{}", self.major, self.minor, self.patch, suffix)?;
-        } else {
-            write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+            write!(f, "-{suffix}")?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{build}")?;
         }
         Ok(())
     }
 }
 
+/// Compares pre-release suffixes as per SemVer 2.0: identifiers are compared
+/// one by one, numerically if both are numeric, lexically otherwise, with
+/// numeric identifiers ranking lower than alphanumeric ones. A version without
+/// a pre-release suffix has a higher precedence than one with it.
+fn compare_suffix(a: &Option<String>, b: &Option<String>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => {
+            let mut a = a.split('.');
+            let mut b = b.split('.');
+            loop {
+                let ordering = match (a.next(), b.next()) {
+                    (None, None) => return Ordering::Equal,
+                    (None, Some(_)) => return Ordering::Less,
+                    (Some(_), None) => return Ordering::Greater,
+                    (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                        (Ok(a), Ok(b)) => a.cmp(&b),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => a.cmp(b),
+                    },
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Validates dot-separated pre-release or build metadata identifiers.
+fn validate_identifiers(s: &str, identifiers: &str, prerelease: bool) -> Result<()> {
+    for identifier in identifiers.split('.') {
+        if identifier.is_empty()
+            || !identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return Err(Error::InvalidVersion(s.to_string()));
+        }
+        if prerelease
+            && identifier.len() > 1
+            && identifier.starts_with('0')
+            && identifier.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(Error::InvalidVersion(s.to_string()));
+        }
+    }
+    Ok(())
+}
+
 impl std::cmp::Ord for Version {
     fn cmp(&self, other: &Self) -> Ordering {
         self.major
             .cmp(&other.major)
             .then(self.minor.cmp(&other.minor))
             .then(self.patch.cmp(&other.patch))
-            .then(other.suffix.cmp(&self.suffix))
+            .then_with(|| compare_suffix(&self.suffix, &other.suffix))
     }
 }
 
@@ -70,10 +209,7 @@ impl PartialOrd for Version {
 
 impl PartialEq for Version {
     fn eq(&self, other: &Self) -> bool {
-        self.major == other.major
-            && self.minor == other.minor
-            && self.patch == other.patch
-            && self.suffix == other.suffix
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -85,82 +221,868 @@ impl FromStr for Version {
         if s == "*" {
             Err(Error::VersionAsterisk)
         } else {
-            let parts = s.split('-').collect::<Vec<_>>();
-            let suffix = if parts.len() == 2 {
-                Some(parts[1].to_owned())
-            } else {
-                None
+            let version = s.trim().trim_start_matches('=').trim_start();
+            if version.starts_with(['^', '~', '<', '>']) {
+                return Err(Error::NonFixedVersion(s.to_string()));
+            }
+            let (version, build) = match version.split_once('+') {
+                Some((version, build)) => {
+                    validate_identifiers(s, build, false)?;
+                    (version, Some(build.to_owned()))
+                }
+                None => (version, None),
+            };
+            let (version, suffix) = match version.split_once('-') {
+                Some((version, suffix)) => {
+                    validate_identifiers(s, suffix, true)?;
+                    (version, Some(suffix.to_owned()))
+                }
+                None => (version, None),
             };
-            
-            let version = parts[0].replace(&['='][..], "");
+
             let v = version.split('.').collect::<Vec<_>>();
             if v.len() != 3 {
                 // log_warn!("Warning","detected non-fixed version: `{s}`");
                 return Err(Error::NonFixedVersion(s.to_string()));
             }
+            if v.iter().any(|n| n.len() > 1 && n.starts_with('0')) {
+                return Err(Error::InvalidVersion(s.to_string()));
+            }
             Ok(Version {
                 major: v[0].parse()?,
                 minor: v[1].parse()?,
                 patch: v[2].parse()?,
                 suffix,
+                build,
             })
         }
     }
 }
 
+/// Pre-release channels in the order of their progression towards a release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Channel {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Channel::Alpha => write!(f, "alpha"),
+            Channel::Beta => write!(f, "beta"),
+            Channel::Rc => write!(f, "rc"),
+        }
+    }
+}
+
+impl FromStr for Channel {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Channel> {
+        match s {
+            "alpha" => Ok(Channel::Alpha),
+            "beta" => Ok(Channel::Beta),
+            "rc" => Ok(Channel::Rc),
+            _ => Err(error!(
+                "unknown pre-release channel `{s}` (expecting 'alpha', 'beta' or 'rc')"
+            )),
+        }
+    }
+}
+
+/// Version level bumped when entering a pre-release channel from a release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Level::Major => write!(f, "major"),
+            Level::Minor => write!(f, "minor"),
+            Level::Patch => write!(f, "patch"),
+        }
+    }
+}
+
+impl From<Level> for Change {
+    fn from(level: Level) -> Change {
+        match level {
+            Level::Major => Change::Major,
+            Level::Minor => Change::Minor,
+            Level::Patch => Change::Patch,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Change {
     Major,
     Minor,
     Patch,
+    /// Enter the given pre-release channel or, if `None`,
+    /// increment the counter of the existing pre-release.
+    Pre(Option<Channel>),
+    /// Bump the given level and enter the pre-release channel
+    /// (`premajor`, `preminor` or `prepatch`).
+    PreBump(Level, Channel),
+    /// Drop the pre-release suffix.
+    Release,
+    /// Infer the change from Conventional Commits since the last release.
+    Auto,
     Custom(Version),
 }
 
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Major => write!(f, "major"),
+            Change::Minor => write!(f, "minor"),
+            Change::Patch => write!(f, "patch"),
+            Change::Pre(None) => write!(f, "pre"),
+            Change::Pre(Some(channel)) => write!(f, "pre {channel}"),
+            Change::PreBump(level, channel) => write!(f, "pre{level} {channel}"),
+            Change::Release => write!(f, "release"),
+            Change::Auto => write!(f, "auto"),
+            Change::Custom(version) => write!(f, "{version}"),
+        }
+    }
+}
+
 impl FromStr for Change {
     type Err = Error;
     fn from_str(s: &str) -> Result<Change> {
-        match s {
-            "major" => Ok(Change::Major),
-            "minor" => Ok(Change::Minor),
-            "patch" => Ok(Change::Patch),
-            _ => Ok(Change::Custom(s.parse()?)),
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["major"] => Ok(Change::Major),
+            ["minor"] => Ok(Change::Minor),
+            ["patch"] => Ok(Change::Patch),
+            ["pre"] => Ok(Change::Pre(None)),
+            ["pre", channel] => Ok(Change::Pre(Some(channel.parse()?))),
+            ["premajor", channel] => Ok(Change::PreBump(Level::Major, channel.parse()?)),
+            ["preminor", channel] => Ok(Change::PreBump(Level::Minor, channel.parse()?)),
+            ["prepatch", channel] => Ok(Change::PreBump(Level::Patch, channel.parse()?)),
+            ["release"] => Ok(Change::Release),
+            ["auto"] => Ok(Change::Auto),
+            [version] => Ok(Change::Custom(version.parse()?)),
+            _ => Err(error!("unable to parse version change `{s}`")),
         }
     }
 }
 
+/// Version change applied to the workspace or to an independent crate.
+#[derive(Debug, Clone)]
+pub struct Bump {
+    /// crate name, `None` for the workspace version
+    pub name: Option<String>,
+    pub to: Version,
+    /// manifests modified by the change
+    pub files: Vec<PathBuf>,
+}
+
 pub struct Versioner {
     ctx: Context,
+    dry_run: bool,
 }
 
 impl Versioner {
-    pub fn new(ctx: Context) -> Versioner {
-        Versioner { ctx }
+    pub fn new(ctx: Context, dry_run: bool) -> Versioner {
+        Versioner { ctx, dry_run }
     }
 
-    pub fn change(&self, change: Change) -> Result<()> {
+    pub fn change(
+        &self,
+        change: Change,
+        packages: Option<Vec<String>>,
+        changed_since: Option<Option<String>>,
+        commit: bool,
+        tag: bool,
+    ) -> Result<()> {
+        let commit = commit || tag;
+        let git = if commit {
+            let git = Git::open(self.ctx.folder())?;
+            if !git.is_clean()? {
+                return Err(
+                    "the working tree contains uncommitted changes, please commit or stash them first"
+                        .into(),
+                );
+            }
+            Some(git)
+        } else {
+            None
+        };
+
+        let mut bumps = vec![];
+
         match &self.ctx {
             Context::Workspace(ctx) => {
-                let mut version = ctx.manifest.version()?;
-                version.change(&change);
-                let mut doc = ctx.manifest.toml.parse::<Document>().unwrap_or_else(|err| {
-                    panic!("toml edit - unable to parse workspace manifest: {err}")
+                if let Some(since) = changed_since {
+                    let mut workspace = false;
+                    for crt in self.changed_crates(ctx, since)? {
+                        if crt.is_independent()? {
+                            bumps.push(self.change_crate(ctx, &change, crt.name())?);
+                        } else {
+                            workspace = true;
+                        }
+                    }
+                    if workspace {
+                        bumps.push(self.change_workspace(ctx, &change)?);
+                    }
+                } else if let Some(packages) = packages {
+                    for package in packages {
+                        bumps.push(self.change_crate(ctx, &change, &package)?);
+                    }
+                } else {
+                    bumps.push(self.change_workspace(ctx, &change)?);
+                }
+            }
+            Context::Crate(ctx) => {
+                if packages.is_some() || changed_since.is_some() {
+                    return Err(
+                        "`--package` and `--changed-since` are only supported in a workspace"
+                            .into(),
+                    );
+                }
+
+                let crt = &ctx.manifest;
+                let current = self.ctx.crate_version(crt)?;
+                let mut version = current.clone();
+                version.change(&self.resolve(&change, &current, None)?)?;
+                let mut doc = load_document(&ctx.file)?;
+
+                if let Some(item) = doc["package"].get_mut("version") {
+                    set_version(item, &version.to_string());
+                }
+
+                self.write(&ctx.file, doc.to_string())?;
+                log_info!("Version", "{current} -> {version}");
+
+                bumps.push(Bump {
+                    name: Some(crt.name().to_string()),
+                    to: version,
+                    files: vec![ctx.file.clone()],
                 });
+            }
+        }
+
+        if bumps.is_empty() {
+            return Ok(());
+        }
+
+        let mut files = bumps
+            .iter()
+            .flat_map(|bump| bump.files.iter().cloned())
+            .collect::<Vec<_>>();
+
+        files.extend(self.update_files(&bumps)?);
+
+        if let Some(config) = self.ctx.changelog()? {
+            files.extend(Changelog::new(self.ctx.clone(), config, self.dry_run).update(&bumps)?);
+        }
+
+        if let Some(git) = git {
+            files.sort();
+            files.dedup();
+            self.commit(&git, &bumps, &files, tag)?;
+        }
+
+        Ok(())
+    }
 
-                let v = version.to_string();
-                doc["workspace"]["package"]["version"] = value(&v);
+    /// Commits the modified files and, if `tag` is set, creates
+    /// annotated release tags for the workspace and the bumped crates.
+    fn commit(&self, git: &Git, bumps: &[Bump], files: &[PathBuf], tag: bool) -> Result<()> {
+        let config = self.ctx.git()?;
 
-                for dep in ctx.manifest.workspace.dependencies.keys() {
-                    doc["workspace"]["dependencies"][dep]["version"] = value(&v);
+        let summary = bumps
+            .iter()
+            .map(|bump| match (&self.ctx, &bump.name) {
+                (Context::Workspace(_), Some(name)) => format!("{name} {}", bump.to),
+                _ => bump.to.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = config.message(&summary);
+
+        let mut tags = vec![];
+        if tag {
+            for bump in bumps {
+                let version = bump.to.to_string();
+                match (&self.ctx, &bump.name) {
+                    (Context::Workspace(ctx), None) => {
+                        if config.tag_template(false).contains("{crate}") {
+                            for crt in ctx.crates.iter() {
+                                if !crt.is_independent()? {
+                                    tags.push((
+                                        config.tag(false, crt.name(), &version),
+                                        version.clone(),
+                                    ));
+                                }
+                            }
+                        } else {
+                            tags.push((config.tag(false, "", &version), version));
+                        }
+                    }
+                    (Context::Workspace(_), Some(name)) => {
+                        tags.push((
+                            config.tag(true, name, &version),
+                            format!("{name} {version}"),
+                        ));
+                    }
+                    (Context::Crate(_), name) => {
+                        let name = name.as_deref().unwrap_or_default();
+                        tags.push((config.tag(false, name, &version), version));
+                    }
                 }
+            }
+        }
 
-                let doc_str = doc.to_string();
-                fs::write(&ctx.manifest.file, doc_str)?;
+        if self.dry_run {
+            log_info!("Dry-run", "skipping commit `{message}`");
+            for (tag, _) in tags {
+                log_info!("Dry-run", "skipping tag `{tag}`");
             }
-            _ => {
-                panic!("not currently supported in a single crate context");
+            return Ok(());
+        }
+
+        git.commit(files, &message)?;
+        log_info!("Commit", "{message}");
+        for (tag, version) in tags {
+            git.tag(&tag, &config.message(&version))?;
+            log_info!("Tag", "{tag}");
+        }
+
+        Ok(())
+    }
+
+    /// Applies new versions to non-Cargo files listed in the `version.files`
+    /// configuration, returning the list of modified files.
+    fn update_files(&self, bumps: &[Bump]) -> Result<Vec<PathBuf>> {
+        let mut files = vec![];
+        let mut unmatched = vec![];
+
+        for entry in self.ctx.version_files()? {
+            let bump = bumps.iter().find(|bump| match &self.ctx {
+                Context::Workspace(_) => bump.name == entry.crate_name,
+                Context::Crate(_) => true,
+            });
+            let Some(bump) = bump else {
+                continue;
+            };
+            let version = bump.to.to_string();
+
+            let re = Regex::new(&entry.pattern)
+                .map_err(|err| error!("invalid version file pattern `{}`: {err}", entry.pattern))?;
+            if re.captures_len() < 2 {
+                return Err(error!(
+                    "version file pattern `{}` must contain a capture group",
+                    entry.pattern
+                ));
             }
+
+            let path = self.ctx.folder().join(&entry.path);
+            let paths = glob::glob(&path.to_string_lossy())
+                .map_err(|err| error!("invalid version file path `{}`: {err}", entry.path))?
+                .filter_map(|path| path.ok())
+                .collect::<Vec<_>>();
+            if paths.is_empty() {
+                unmatched.push(format!("`{}`: no files found", entry.path));
+            }
+
+            for file in paths {
+                let text = fs::read_to_string(&file)?;
+                match replace_captures(&re, &text, &version) {
+                    Some(updated) => {
+                        if updated != text {
+                            self.write(&file, updated)?;
+                            log_info!("Version", "{version} in `{}`", file.display());
+                            files.push(file);
+                        }
+                    }
+                    None => unmatched.push(format!(
+                        "`{}`: pattern `{}` not found",
+                        file.display(),
+                        entry.pattern
+                    )),
+                }
+            }
+        }
+
+        for unmatched in unmatched {
+            log_warn!("Version", "{unmatched}");
         }
 
+        Ok(files)
+    }
+
+    /// Writes the file unless running in the dry-run mode.
+    fn write(&self, file: &Path, contents: String) -> Result<()> {
+        if self.dry_run {
+            log_info!("Dry-run", "skipping update of `{}`", file.display());
+        } else {
+            fs::write(file, contents)?;
+        }
         Ok(())
     }
+
+    /// Resolves the `auto` change from the commits made since the last release
+    /// tag (limited to the given independent crate); other changes are returned as is.
+    fn resolve(&self, change: &Change, version: &Version, crt: Option<&Crate>) -> Result<Change> {
+        if !matches!(change, Change::Auto) {
+            return Ok(change.clone());
+        }
+
+        let git = Git::open(self.ctx.folder())?;
+        let pattern = self.ctx.git()?.tag_pattern(crt.map(Crate::name));
+        let tag = git.last_tag(&pattern)?;
+        let commits = git.commits(tag.as_deref(), crt.map(|crt| crt.folder.as_path()))?;
+        infer_change(&commits, version)
+    }
+
+    /// Bumps the workspace version shared by all crates
+    /// that are not versioned independently.
+    fn change_workspace(&self, ctx: &Arc<WorkspaceContext>, change: &Change) -> Result<Bump> {
+        let current = ctx.manifest.version()?;
+        let mut version = current.clone();
+        version.change(&self.resolve(change, &current, None)?)?;
+        let mut doc = load_document(&ctx.manifest.file)?;
+
+        let v = version.to_string();
+        doc["workspace"]["package"]["version"] = value(&v);
+
+        for dep in ctx.manifest.workspace.dependencies.keys() {
+            let independent = ctx
+                .crates
+                .iter()
+                .find(|crt| crt.name() == dep)
+                .map(Crate::is_independent)
+                .transpose()?
+                .unwrap_or(false);
+            if !independent {
+                doc["workspace"]["dependencies"][dep]["version"] = value(&v);
+            }
+        }
+
+        let doc_str = doc.to_string();
+        self.write(&ctx.manifest.file, doc_str)?;
+        log_info!("Version", "{current} -> {version}");
+
+        Ok(Bump {
+            name: None,
+            to: version,
+            files: vec![ctx.manifest.file.clone()],
+        })
+    }
+
+    /// Bumps the version of an independently versioned workspace member
+    /// and updates it wherever the workspace or other members depend on it.
+    fn change_crate(
+        &self,
+        ctx: &Arc<WorkspaceContext>,
+        change: &Change,
+        name: &str,
+    ) -> Result<Bump> {
+        let crt = ctx
+            .crates
+            .iter()
+            .find(|crt| crt.name() == name)
+            .ok_or_else(|| error!("`{name}` is not a published workspace member"))?;
+        if !crt.is_independent()? {
+            return Err(error!(
+                "`{name}` is not versioned independently (missing `package.metadata.emanate.independent`)"
+            ));
+        }
+        let current = crt.package.explicit_version()?.ok_or_else(|| {
+            error!("`{name}` is versioned independently but inherits the workspace version")
+        })?;
+        let mut version = current.clone();
+        version.change(&self.resolve(change, &current, Some(crt))?)?;
+        let v = version.to_string();
+
+        let mut doc = load_document(&crt.file)?;
+        if let Some(item) = doc["package"].get_mut("version") {
+            set_version(item, &v);
+        }
+        self.write(&crt.file, doc.to_string())?;
+
+        let mut doc = load_document(&ctx.manifest.file)?;
+        if let Some(deps) = doc["workspace"]
+            .get_mut("dependencies")
+            .and_then(Item::as_table_like_mut)
+        {
+            update_dependencies(deps, name, &v);
+        }
+        self.write(&ctx.manifest.file, doc.to_string())?;
+
+        let mut files = vec![crt.file.clone(), ctx.manifest.file.clone()];
+        for file in ctx.member_manifests() {
+            if file == crt.file {
+                continue;
+            }
+            let mut doc = load_document(&file)?;
+            if update_dependency_tables(doc.as_table_mut(), name, &v) > 0 {
+                self.write(&file, doc.to_string())?;
+                log_info!("Dependency", "{name} -> {v} in `{}`", file.display());
+                files.push(file);
+            }
+        }
+
+        log_info!("Version", "{name} {current} -> {version}");
+
+        Ok(Bump {
+            name: Some(name.to_string()),
+            to: version,
+            files,
+        })
+    }
+
+    /// Crates (in the publishing order) with files changed since the given git
+    /// reference (the last `v*` tag by default), followed by every crate that
+    /// depends on them, directly or transitively.
+    fn changed_crates<'ctx>(
+        &self,
+        ctx: &'ctx Arc<WorkspaceContext>,
+        since: Option<String>,
+    ) -> Result<Vec<&'ctx Crate>> {
+        let git = Git::open(&ctx.folder)?;
+        let since = match since {
+            Some(since) => since,
+            None => {
+                let pattern = self.ctx.git()?.tag_pattern(None);
+                git.last_tag(&pattern)?.ok_or_else(|| {
+                    error!("no `{pattern}` tags found, please specify `--changed-since <git-ref>`")
+                })?
+            }
+        };
+
+        let mut changed = Vec::<&str>::new();
+        for file in git.changed_files(&since)? {
+            // files belong to the innermost member folder containing them
+            let owner = ctx
+                .crates
+                .iter()
+                .filter(|crt| file.starts_with(&crt.folder))
+                .max_by_key(|crt| crt.folder.components().count());
+            if let Some(crt) = owner {
+                if !changed.contains(&crt.name()) {
+                    changed.push(crt.name());
+                }
+            }
+        }
+
+        // dependencies precede their dependents in the publishing order
+        let mut selected = Vec::<&Crate>::new();
+        for crt in ctx.crates.iter() {
+            let dependent = crt
+                .dependencies
+                .keys()
+                .chain(crt.build_dependencies.keys())
+                .any(|dep| selected.iter().any(|sel| sel.name() == dep));
+            if changed.contains(&crt.name()) {
+                log_info!("Changed", "{} (since {since})", crt.name());
+                selected.push(crt);
+            } else if dependent {
+                log_info!("Dependent", "{}", crt.name());
+                selected.push(crt);
+            }
+        }
+
+        if selected.is_empty() {
+            log_info!("Version", "no crates changed since {since}");
+        }
+
+        Ok(selected)
+    }
+}
+
+/// Replaces the first capture group of every match with the given version,
+/// returning `None` if the pattern does not match.
+fn replace_captures(re: &Regex, text: &str, version: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut last = 0;
+    let mut matched = false;
+    for captures in re.captures_iter(text) {
+        if let Some(m) = captures.get(1) {
+            result.push_str(&text[last..m.start()]);
+            result.push_str(version);
+            last = m.end();
+            matched = true;
+        }
+    }
+    if !matched {
+        return None;
+    }
+    result.push_str(&text[last..]);
+    Some(result)
+}
+
+pub fn load_document(file: &Path) -> Result<Document> {
+    let toml = fs::read_to_string(file)?;
+    toml.parse::<Document>()
+        .map_err(|err| error!("unable to parse `{}`: {err}", file.display()))
+}
+
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Updates the version of the dependency `name` in all dependency
+/// tables of a crate manifest, including target-specific ones.
+/// Returns the number of updated entries.
+pub fn update_dependency_tables(table: &mut dyn TableLike, name: &str, version: &str) -> usize {
+    let mut count = 0;
+    for kind in DEPENDENCY_TABLES {
+        if let Some(deps) = table.get_mut(kind).and_then(Item::as_table_like_mut) {
+            count += update_dependencies(deps, name, version);
+        }
+    }
+    if let Some(targets) = table.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(target) = target.as_table_like_mut() {
+                count += update_dependency_tables(target, name, version);
+            }
+        }
+    }
+    count
+}
+
+/// Updates the version of the dependency `name` (taking `package`
+/// renames into account) in a single dependency table.
+pub fn update_dependencies(deps: &mut dyn TableLike, name: &str, version: &str) -> usize {
+    let mut count = 0;
+    for (key, item) in deps.iter_mut() {
+        let package = item
+            .get("package")
+            .and_then(Item::as_str)
+            .unwrap_or(key.get())
+            .to_string();
+        if package == name && set_version(item, version) {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Sets the version of a `name = "x.y.z"` or `name = { version = "x.y.z" }`
+/// entry, keeping the `=` prefix and the formatting of the existing value.
+/// Returns `false` if the entry has no version (i.e. `workspace = true`).
+fn set_version(item: &mut Item, version: &str) -> bool {
+    let value = if item.is_str() {
+        item.as_value_mut()
+    } else {
+        item.get_mut("version").and_then(Item::as_value_mut)
+    };
+    match value {
+        Some(value) => {
+            let Some(current) = value.as_str() else {
+                return false;
+            };
+            let prefix = if current.trim_start().starts_with('=') {
+                "="
+            } else {
+                ""
+            };
+            let decor = value.decor().clone();
+            *value = format!("{prefix}{version}").into();
+            *value.decor_mut() = decor;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    fn changed(from: &str, change: &str) -> Result<String> {
+        let mut version = version(from);
+        version.change(&change.parse()?)?;
+        Ok(version.to_string())
+    }
+
+    #[test]
+    fn pre_release_precedence() {
+        assert!(version("1.0.0-rc.2") < version("1.0.0-rc.10"));
+        assert!(version("1.0.0-alpha") < version("1.0.0-alpha.1"));
+        assert!(version("1.0.0-alpha.1") < version("1.0.0"));
+        assert!(version("1.0.0-alpha.1") < version("1.0.0-alpha.beta"));
+        assert!(version("1.0.0-1") < version("1.0.0-alpha"));
+        assert_eq!(
+            compare_suffix(&Some("9".to_string()), &Some("a".to_string())),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn build_metadata_is_ignored() {
+        assert_eq!(version("1.0.0+b.1"), version("1.0.0+b.2"));
+        assert_eq!(version("1.0.0-rc.1+b.1"), version("1.0.0-rc.1"));
+        assert_eq!(
+            version("1.0.0+b.2").cmp(&version("1.0.0+b.1")),
+            Ordering::Equal
+        );
+        assert!(version("1.0.0-rc.1+zzz") < version("1.0.0+aaa"));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        assert_eq!(version("1.0.0-rc-1+b.1").to_string(), "1.0.0-rc-1+b.1");
+        let v = version("1.0.0-rc-1+b.1");
+        assert_eq!(v.suffix.as_deref(), Some("rc-1"));
+        assert_eq!(v.build.as_deref(), Some("b.1"));
+    }
+
+    #[test]
+    fn invalid_versions() {
+        for s in [
+            "01.0.0",
+            "1.00.0",
+            "1.0.01",
+            "1.0.0-rc.01",
+            "1.0.0-",
+            "1.0.0+",
+            "1.0.0-a..b",
+        ] {
+            assert!(
+                matches!(s.parse::<Version>(), Err(Error::InvalidVersion(_))),
+                "{s}"
+            );
+        }
+        assert!(version("1.0.0-0").suffix.is_some());
+        assert!(version("1.0.0+001").build.is_some());
+        for s in ["1", "1.0", "^1.0.0", "~1.0.0", ">1.0.0", "<1.0.0"] {
+            assert!(
+                matches!(s.parse::<Version>(), Err(Error::NonFixedVersion(_))),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn replace_captures_at_start() {
+        let re = Regex::new(r"^(\d+\.\d+\.\d+)").unwrap();
+        assert_eq!(
+            replace_captures(&re, "1.0.0 release", "1.1.0").as_deref(),
+            Some("1.1.0 release")
+        );
+        let re = Regex::new(r"^v?(\d*)").unwrap();
+        assert_eq!(replace_captures(&re, "", "1.1.0").as_deref(), Some("1.1.0"));
+        let re = Regex::new(r"version = (\S+)").unwrap();
+        assert_eq!(replace_captures(&re, "nothing here", "1.1.0"), None);
+    }
+
+    const MANIFEST: &str = r#"[package]
+name = "app"
+
+[dependencies]
+serde = "1.0.100" # latest
+log = { version = "=0.4.1", features = ["std"] }
+json = { package = "serde_json", version = "1.0.1" }
+local = { path = "../local" }
+rand.version = "0.8.1"
+rand.default-features = false
+
+[dependencies.anyhow]
+# pinned
+version = "1.0.50"   # trailing
+default-features = false
+
+[dev-dependencies]
+serde.workspace = true
+
+[target.'cfg(unix)'.dependencies]
+serde = { version = "1.0.100", optional = true }
+
+[target.'cfg(windows)'.build-dependencies.serde]
+version = "=1.0.100"
+"#;
+
+    #[test]
+    fn update_dependency_versions() {
+        let mut doc = MANIFEST.parse::<Document>().unwrap();
+        let table = doc.as_table_mut();
+        assert_eq!(update_dependency_tables(table, "serde", "1.0.200"), 3);
+        assert_eq!(update_dependency_tables(table, "log", "0.4.9"), 1);
+        assert_eq!(update_dependency_tables(table, "serde_json", "1.0.2"), 1);
+        assert_eq!(update_dependency_tables(table, "json", "9.9.9"), 0);
+        assert_eq!(update_dependency_tables(table, "local", "9.9.9"), 0);
+        assert_eq!(update_dependency_tables(table, "rand", "0.8.5"), 1);
+        assert_eq!(update_dependency_tables(table, "anyhow", "1.0.80"), 1);
+        assert_eq!(
+            doc.to_string(),
+            r#"[package]
+name = "app"
+
+[dependencies]
+serde = "1.0.200" # latest
+log = { version = "=0.4.9", features = ["std"] }
+json = { package = "serde_json", version = "1.0.2" }
+local = { path = "../local" }
+rand.version = "0.8.5"
+rand.default-features = false
+
+[dependencies.anyhow]
+# pinned
+version = "1.0.80"   # trailing
+default-features = false
+
+[dev-dependencies]
+serde.workspace = true
+
+[target.'cfg(unix)'.dependencies]
+serde = { version = "1.0.200", optional = true }
+
+[target.'cfg(windows)'.build-dependencies.serde]
+version = "=1.0.200"
+"#
+        );
+    }
+
+    #[test]
+    fn update_workspace_dependencies() {
+        let manifest = r#"[workspace.dependencies]
+serde = { version = "=1.0.100", features = ["derive"] } # pinned
+json = { package = "serde_json", version = "1.0.1" }
+"#;
+        let mut doc = manifest.parse::<Document>().unwrap();
+        let deps = doc["workspace"]
+            .get_mut("dependencies")
+            .and_then(Item::as_table_like_mut)
+            .unwrap();
+        assert_eq!(update_dependencies(deps, "serde", "1.0.200"), 1);
+        assert_eq!(update_dependencies(deps, "serde_json", "1.0.2"), 1);
+        assert_eq!(
+            doc.to_string(),
+            r#"[workspace.dependencies]
+serde = { version = "=1.0.200", features = ["derive"] } # pinned
+json = { package = "serde_json", version = "1.0.2" }
+"#
+        );
+    }
+
+    #[test]
+    fn pre_release_lifecycle() {
+        assert_eq!(changed("1.3.2", "pre alpha").unwrap(), "1.3.3-alpha.1");
+        assert_eq!(changed("1.3.2", "preminor alpha").unwrap(), "1.4.0-alpha.1");
+        assert_eq!(changed("1.3.2", "premajor rc").unwrap(), "2.0.0-rc.1");
+        assert_eq!(changed("1.3.2", "prepatch beta").unwrap(), "1.3.3-beta.1");
+        assert_eq!(changed("1.4.0-alpha.1", "pre").unwrap(), "1.4.0-alpha.2");
+        assert_eq!(
+            changed("1.4.0-alpha.2", "pre beta").unwrap(),
+            "1.4.0-beta.1"
+        );
+        assert_eq!(changed("1.4.0-rc.2", "release").unwrap(), "1.4.0");
+        assert!(changed("1.4.0-beta.1", "pre alpha").is_err());
+        assert!(changed("1.0.0-dev.3", "pre alpha").is_err());
+        assert_eq!(changed("1.0.0-dev.3", "pre rc").unwrap(), "1.0.0-rc.1");
+        assert!(changed("1.4.0", "pre").is_err());
+        assert!(changed("1.4.0", "release").is_err());
+    }
 }
@@ -0,0 +1,128 @@
+use crate::prelude::*;
+
+/// Executes `cargo yank` on all workspace crates (or a standalone crate): <https://doc.rust-lang.org/cargo/commands/cargo-yank.html>
+///
+/// Crates are processed in the reverse publishing order, so that dependents
+/// are yanked before their dependencies. Crates that never published the
+/// version are skipped.
+pub struct Yanker {
+    ctx: Context,
+    /// registry requested using `--registry`
+    registry: Option<String>,
+}
+
+impl Yanker {
+    pub fn new(ctx: Context, registry: Option<String>) -> Self {
+        Self { ctx, registry }
+    }
+
+    pub async fn yank(
+        &self,
+        version: Version,
+        undo: bool,
+        packages: Option<Vec<String>>,
+        dry_run: bool,
+    ) -> Result<()> {
+        let crates = self
+            .ctx
+            .layers()
+            .into_iter()
+            .flatten()
+            .rev()
+            .collect::<Vec<_>>();
+        if let Some(packages) = packages.as_ref() {
+            for package in packages {
+                if !crates.iter().any(|crt| crt.name() == package) {
+                    return Err(error!("package `{package}` not found"));
+                }
+            }
+        }
+
+        let (verb, action, descr) = if undo {
+            ("unyank", "unyanked", "Unyanking")
+        } else {
+            ("yank", "yanked", "Yanking")
+        };
+
+        let registries = self.ctx.registries(self.registry.as_deref())?;
+        let mut clients: HashMap<Option<String>, CratesIo> = HashMap::new();
+        let mut summary = vec![];
+        let mut failures = 0;
+        for crt in crates {
+            let project = crt.name();
+            if let Some(packages) = packages.as_ref() {
+                if !packages.iter().any(|package| package == project) {
+                    continue;
+                }
+            }
+            let Some(registry) = registries.get(project) else {
+                continue;
+            };
+
+            if !clients.contains_key(&registry.name) {
+                clients.insert(registry.name.clone(), CratesIo::with_registry(registry)?);
+            }
+            let published = clients[&registry.name].get_crate(project).await?;
+            let yanked = published.as_ref().and_then(|published| {
+                published
+                    .versions
+                    .iter()
+                    .find(|v| v.version == version)
+                    .map(|v| v.yanked)
+            });
+
+            let outcome = match yanked {
+                None => style(format!("skipped - {version} not published")).dim(),
+                Some(yanked) if yanked != undo => style(
+                    if undo {
+                        "skipped - not yanked"
+                    } else {
+                        "skipped - already yanked"
+                    }
+                    .to_string(),
+                )
+                .dim(),
+                Some(_) if dry_run => {
+                    log_info!(descr, "{project} {version} ({registry})");
+                    style(format!("to be {action}")).yellow()
+                }
+                Some(_) => {
+                    log_info!(descr, "{project} {version} ({registry})");
+                    let version = version.to_string();
+                    let mut args = vec!["yank", project, "--version", &version];
+                    if undo {
+                        args.push("--undo");
+                    }
+                    args.extend(registry.args());
+                    match cmd("cargo", args).dir(self.ctx.folder()).run() {
+                        Ok(_) => style(action.to_string()).green(),
+                        Err(err) => {
+                            failures += 1;
+                            log_error!(descr, "{project} -> {err}");
+                            style("failed".to_string()).red()
+                        }
+                    }
+                }
+            };
+            summary.push((project, outcome));
+        }
+
+        let len = summary
+            .iter()
+            .map(|(project, _)| project.len())
+            .fold(0, |a, b| a.max(b));
+        for (project, outcome) in summary.iter() {
+            log_info!(
+                "Summary",
+                "{} {version} {outcome}",
+                project.pad(len, ' ', Alignment::Left, false)
+            );
+        }
+
+        if failures > 0 {
+            return Err(error!("failed to {verb} {failures} crates"));
+        }
+
+        Ok(())
+    }
+}